@@ -39,7 +39,7 @@ impl Lexer {
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum S {
     Atom(char),
-    Cons(char, Vec<S>),
+    Cons(char, Vec<Self>),
 }
 
 impl fmt::Display for S {
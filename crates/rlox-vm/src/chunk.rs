@@ -22,6 +22,8 @@ pub enum OpCode {
     OpSetLocal,
     OpSetGlobal,
     OpDefaineGlobal,
+    /// Read a property of the object on top, followed by the name constant
+    OpGetProperty,
     OpEqual,
     OpGreater,
     OpLess,
@@ -29,14 +31,27 @@ pub enum OpCode {
     OpSubtract,
     OpMultiply,
     OpDivide,
+    OpModulo,
+    OpPower,
     OpNot,
     OpNegate,
+    OpToString,
     OpPrint,
     // OpConstantLong,
     OpReturn,
     OpJumpIfFalse,
     OpJump,
     OpLoop,
+    /// Push a handler, followed by the catch and the finally offsets, a catch offset of 0 means
+    /// no `catch` clause
+    OpTry,
+    /// Pop the innermost handler and run its `finally` normally
+    OpEndTry,
+    OpThrow,
+    /// Resume the completion the `finally` was entered with
+    OpEndFinally,
+    /// Run the `finally` of the given number of handlers before going on, for `continue`
+    OpUnwind,
 }
 
 impl From<OpCode> for u8 {
@@ -59,7 +74,7 @@ pub struct Chunk {
     // `count`, `capacity`, rust direct use `Vec`
     pub code: Vec<u8>,
     constants: ValueArray,
-    // (count, line)
+    // (start offset, line), one entry per run of same line code
     lines: Vec<(usize, usize)>,
 }
 
@@ -74,10 +89,9 @@ impl Chunk {
 
     pub fn write<V: Into<u8>>(&mut self, value: V, line: usize) {
         self.code.push(value.into());
-        let last = self.lines.last_mut();
-        match last {
-            Some((count, line_)) if *line_ == line => *count += 1,
-            _ => self.lines.push((1, line)),
+        match self.lines.last() {
+            Some(&(_, line_)) if line_ == line => {},
+            _ => self.lines.push((self.code.len() - 1, line)),
         }
     }
 
@@ -91,19 +105,18 @@ impl Chunk {
         unimplemented!()
     }
 
-    pub fn count(&self) -> usize {
+    pub const fn count(&self) -> usize {
         self.code.len()
     }
 
     pub fn get_line(&self, offset: usize) -> usize {
-        let mut cur = 0;
-        for &(count, cur_line) in &self.lines {
-            cur += count;
-            if offset < cur {
-                return cur_line;
-            }
+        if offset >= self.code.len() {
+            return 0;
         }
-        0
+        let run = self.lines.partition_point(|&(start, _)| start <= offset);
+        self.lines
+            .get(run.wrapping_sub(1))
+            .map_or(0, |&(_, line)| line)
     }
 
     pub fn code(&self) -> &[u8] {
@@ -147,13 +160,42 @@ impl Chunk {
             v @ (OpCode::OpDefaineGlobal
             | OpCode::OpConstant
             | OpCode::OpSetGlobal
-            | OpCode::OpGetGlobal) => self.constant_instruction(v, offset),
-            v @ (OpCode::OpGetLocal | OpCode::OpSetLocal) => self.byte_instruction(v, offset),
+            | OpCode::OpGetGlobal
+            | OpCode::OpGetProperty) => self.constant_instruction(v, offset),
+            v @ (OpCode::OpGetLocal | OpCode::OpSetLocal | OpCode::OpUnwind) => {
+                self.byte_instruction(v, offset)
+            },
             v @ (OpCode::OpJump | OpCode::OpJumpIfFalse) => self.jump_instruction(v, 1, offset),
             v @ OpCode::OpLoop => self.jump_instruction(v, -1, offset),
+            v @ OpCode::OpTry => self.try_instruction(v, offset),
             v => Self::simple_instruction(v, offset),
         }
     }
+
+    /// Every instruction's opcode in order, without the operands
+    pub fn opcodes(&self) -> Vec<OpCode> {
+        let mut opcodes = vec![];
+        let mut offset = 0;
+        while offset < self.code.len() {
+            let opcode = self.code[offset].into();
+            offset += match opcode {
+                OpCode::OpDefaineGlobal
+                | OpCode::OpConstant
+                | OpCode::OpSetGlobal
+                | OpCode::OpGetGlobal
+                | OpCode::OpGetProperty
+                | OpCode::OpGetLocal
+                | OpCode::OpSetLocal
+                | OpCode::OpUnwind => 2,
+                OpCode::OpJump | OpCode::OpJumpIfFalse | OpCode::OpLoop => 3,
+                OpCode::OpTry => 5,
+                _ => 1,
+            };
+            opcodes.push(opcode);
+        }
+        opcodes
+    }
+
     fn jump_instruction(&self, name: OpCode, sign: i8, offset: usize) -> usize {
         let jump = u16::from_be_bytes([self.code[offset + 1], self.code[offset + 2]]);
         println!(
@@ -162,6 +204,22 @@ impl Chunk {
         );
         offset + 3
     }
+    fn try_instruction(&self, name: OpCode, offset: usize) -> usize {
+        let catch = u16::from_be_bytes([self.code[offset + 1], self.code[offset + 2]]);
+        let finally = u16::from_be_bytes([self.code[offset + 3], self.code[offset + 4]]);
+        let base = offset + 5;
+        if catch == 0 {
+            println!("{name} {offset:>4} -> none, {}", base + finally as usize);
+        }
+        else {
+            println!(
+                "{name} {offset:>4} -> {}, {}",
+                base + catch as usize,
+                base + finally as usize
+            );
+        }
+        offset + 5
+    }
     fn byte_instruction(&self, name: OpCode, offset: usize) -> usize {
         let slot = self.code[offset + 1];
         println!("{:<16} {:>4}", name, slot);
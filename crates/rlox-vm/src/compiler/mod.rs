@@ -1,7 +1,9 @@
 #![expect(clippy::unwrap_used, reason = "shit")]
 pub mod rule;
 
-use std::{cell::RefCell, convert::Into, hint::unreachable_unchecked, ptr::NonNull};
+use std::{
+    cell::RefCell, collections::HashSet, convert::Into, hint::unreachable_unchecked, ptr::NonNull,
+};
 
 use itertools::PeekNth;
 use rlox::token::{Token, TokenInner};
@@ -30,6 +32,16 @@ where
     had_error: bool,
     panic_mode: bool,
     cur_compiler: CompilerLink,
+    /// Where `continue` jumps, the increment clause of a `for` or the condition
+    innermost_loop_start: Option<usize>,
+    /// Scope depth of the innermost loop, `continue` pops the locals declared deeper
+    innermost_loop_depth: usize,
+    /// Number of `try` statements around the innermost loop, `continue` unwinds the ones inside
+    innermost_loop_tries: usize,
+    /// Local count at each enclosing `try` body or `catch` clause, innermost last
+    try_locals: Vec<usize>,
+    /// Globals declared with `const`
+    const_globals: HashSet<String>,
 }
 
 #[derive(Clone, Copy)]
@@ -47,15 +59,17 @@ pub enum CurFunType {
 #[derive(PartialEq, Eq, PartialOrd, Ord)]
 pub enum Precedence {
     None,
-    Assignment, // =
-    Or,         // or
-    And,        // and
-    Equality,   // == !=
-    Comparison, // < > <= >=
-    Term,       // + -
-    Factor,     // * /
-    Unary,      // ! -
-    Call,       // . ()
+    Assignment,  // =
+    Conditional, // ?:
+    Or,          // or
+    And,         // and
+    Equality,    // == !=
+    Comparison,  // < > <= >=
+    Term,        // + -
+    Factor,      // * /
+    Unary,       // ! -
+    Power,       // **
+    Call,        // . ()
     Primary,
 }
 impl From<u8> for Precedence {
@@ -76,6 +90,8 @@ impl From<Precedence> for u8 {
 pub struct Local {
     name: Token,
     depth: i32,
+    /// declared with `const`
+    is_const: bool,
 }
 
 type CompilerLink = Option<NonNull<Compiler>>;
@@ -97,13 +113,14 @@ impl Compiler {
     where
         I: std::iter::Iterator<Item = rlox::token::Token>,
     {
-        let mut compiler = Self {
+        let compiler = Self {
             enclosing: parser.cur_compiler,
             locals: vec![Local {
                 name: Token::Invalid {
                     inner: TokenInner::default(),
                 },
                 depth: 0,
+                is_const: false,
             }],
             // locals: vec![
             //     Local {
@@ -122,9 +139,10 @@ impl Compiler {
                     String::new()
                 }
                 else {
-                    unsafe { parser.previous.as_ref().unwrap_unchecked() }
-                        .lexeme()
-                        .to_owned()
+                    match unsafe { parser.previous.as_ref().unwrap_unchecked() } {
+                        Token::Fun { .. } => "anonymous".to_owned(),
+                        name => name.lexeme().to_owned(),
+                    }
                 },
             },
             cur_fn_typ,
@@ -217,6 +235,33 @@ where
         self.emit_constant(Value::Obj(Obj::String(previous.lexeme().to_owned())))
     }
 
+    /// `"a ${b} c"` compile to `"a " + str(b) + " c"`
+    fn interpolation(&mut self, _: bool) -> Result<()> {
+        self.string(false)?;
+        loop {
+            self.expression()?;
+            self.emit_bytes(OpCode::OpToString, OpCode::OpAdd);
+
+            let is_tail = match &self.current {
+                Some(Token::Interpolation { .. }) => false,
+                Some(Token::String { .. }) => true,
+                t => {
+                    return error::NotMatchSnafu {
+                        msg: "Expect '}' after interpolated expression",
+                        token: t.clone(),
+                    }
+                    .fail();
+                },
+            };
+            self.advance();
+            self.string(false)?;
+            self.emit_byte(OpCode::OpAdd);
+            if is_tail {
+                return Ok(());
+            }
+        }
+    }
+
     fn variable(&mut self, can_assign: bool) -> Result<()> {
         let Some(name) = self.previous.clone()
         else {
@@ -226,29 +271,113 @@ where
     }
 
     fn named_variable(&mut self, name: &Token, can_assign: bool) -> Result<()> {
-        let (get_op, set_op);
-        let arg = self.resolve_local(name)?;
-        let arg = if arg == -1 {
-            get_op = OpCode::OpGetGlobal;
-            set_op = OpCode::OpSetGlobal;
-            Self::make_constant(Value::Obj(Obj::String(name.lexeme().to_owned())))?
+        let (get_op, set_op, arg) = self.variable_ops(name)?;
+        let assigns = matches!(
+            self.current,
+            Some(Token::Equal { .. } | Token::PlusPlus { .. } | Token::MinusMinus { .. })
+        ) || self.current.as_ref().and_then(Self::compound_op).is_some();
+        if can_assign && assigns && self.is_const(name) {
+            return error::AssignConstSnafu { name: name.clone() }.fail();
         }
-        else {
-            get_op = OpCode::OpGetLocal;
-            set_op = OpCode::OpSetLocal;
-            arg as u8
-        };
 
         if can_assign && matches!(self.current, Some(Token::Equal { .. })) {
             self.advance();
             self.expression()?;
             self.emit_bytes(set_op, arg);
         }
+        else if can_assign && let Some(op) = self.current.as_ref().and_then(Self::compound_op) {
+            // `a op= b` is `a = a op b`
+            self.advance();
+            self.emit_bytes(get_op, arg);
+            self.expression()?;
+            self.emit_byte(op);
+            self.emit_bytes(set_op, arg);
+        }
+        else if let Some(tk @ (Token::PlusPlus { .. } | Token::MinusMinus { .. })) = &self.current
+        {
+            // `a++` is `(a += 1) - 1`
+            let (op, undo) = if matches!(tk, Token::PlusPlus { .. }) {
+                (OpCode::OpAdd, OpCode::OpSubtract)
+            }
+            else {
+                (OpCode::OpSubtract, OpCode::OpAdd)
+            };
+            self.advance();
+            self.emit_bytes(get_op, arg);
+            self.emit_constant(Value::Number(1.0))?;
+            self.emit_byte(op);
+            self.emit_bytes(set_op, arg);
+            self.emit_constant(Value::Number(1.0))?;
+            self.emit_byte(undo);
+        }
         else {
             self.emit_bytes(get_op, arg);
         }
         Ok(())
     }
+
+    /// `++a`, `--a`
+    fn prefix_increment(&mut self, _: bool) -> Result<()> {
+        let op = match self.previous {
+            Some(Token::PlusPlus { .. }) => OpCode::OpAdd,
+            _ => OpCode::OpSubtract,
+        };
+        self.consume_ident()?;
+        let Some(name) = self.previous.clone()
+        else {
+            return error::MissingPrevSnafu.fail();
+        };
+        if self.is_const(&name) {
+            return error::AssignConstSnafu { name }.fail();
+        }
+        let (get_op, set_op, arg) = self.variable_ops(&name)?;
+        self.emit_bytes(get_op, arg);
+        self.emit_constant(Value::Number(1.0))?;
+        self.emit_byte(op);
+        self.emit_bytes(set_op, arg);
+        Ok(())
+    }
+
+    /// The innermost variable named `name` is declared with `const`
+    fn is_const(&self, name: &Token) -> bool {
+        self.cur_compiler()
+            .and_then(|compiler| {
+                compiler
+                    .locals
+                    .iter()
+                    .rev()
+                    .find(|local| local.name.lexeme() == name.lexeme())
+            })
+            .map_or_else(
+                || self.const_globals.contains(name.lexeme()),
+                |local| local.is_const,
+            )
+    }
+
+    /// The get and set instructions of a variable with their operand
+    fn variable_ops(&self, name: &Token) -> Result<(OpCode, OpCode, u8)> {
+        let arg = self.resolve_local(name)?;
+        if arg == -1 {
+            let arg = Self::make_constant(Value::Obj(Obj::String(name.lexeme().to_owned())))?;
+            Ok((OpCode::OpGetGlobal, OpCode::OpSetGlobal, arg))
+        }
+        else {
+            Ok((OpCode::OpGetLocal, OpCode::OpSetLocal, arg as u8))
+        }
+    }
+
+    /// The binary instruction of a compound assignment token
+    const fn compound_op(token: &Token) -> Option<OpCode> {
+        match token {
+            Token::PlusEqual { .. } => Some(OpCode::OpAdd),
+            Token::MinusEqual { .. } => Some(OpCode::OpSubtract),
+            Token::StarEqual { .. } => Some(OpCode::OpMultiply),
+            Token::SlashEqual { .. } => Some(OpCode::OpDivide),
+            Token::PercentEqual { .. } => Some(OpCode::OpModulo),
+            _ => None,
+        }
+    }
+
     fn resolve_local(&self, name: &Token) -> Result<i32> {
         let Some(compiler) = self.cur_compiler()
         else {
@@ -283,13 +412,28 @@ where
         Ok(())
     }
 
+    /// `object.name`, only the `message` and `line` of a caught error can be read for now
+    fn dot(&mut self, _: bool) -> Result<()> {
+        self.consume_ident()?;
+        let name = unsafe { self.previous.as_ref().unwrap_unchecked() };
+        let name = Self::make_constant(Value::Obj(Obj::String(name.lexeme().to_owned())))?;
+        self.emit_bytes(OpCode::OpGetProperty, name);
+        Ok(())
+    }
+
     fn binary(&mut self, _: bool) -> Result<()> {
         let Some(op_type) = self.previous.clone()
         else {
             return error::MissingPrevSnafu.fail();
         };
         let rule: ParseRule<I> = get_rule(&op_type);
-        self.parse_precedence((Into::<u8>::into(rule.precedence) + 1_u8).into())?;
+        if matches!(op_type, Token::StarStar { .. }) {
+            // right associative, and the exponent can be negated: `2 ** -1`
+            self.parse_precedence(Precedence::Unary)?;
+        }
+        else {
+            self.parse_precedence((Into::<u8>::into(rule.precedence) + 1_u8).into())?;
+        }
         match op_type {
             Token::BangEqual { .. } => self.emit_bytes(OpCode::OpEqual, OpCode::OpNot),
             Token::EqualEqual { .. } => self.emit_byte(OpCode::OpEqual),
@@ -301,6 +445,8 @@ where
             Token::Minus { .. } => self.emit_byte(OpCode::OpSubtract),
             Token::Star { .. } => self.emit_byte(OpCode::OpMultiply),
             Token::Slash { .. } => self.emit_byte(OpCode::OpDivide),
+            Token::Percent { .. } => self.emit_byte(OpCode::OpModulo),
+            Token::StarStar { .. } => self.emit_byte(OpCode::OpPower),
             _ => unsafe { unreachable_unchecked() },
         }
         Ok(())
@@ -350,7 +496,10 @@ where
             };
             infix_rule(self, can_assign)?;
         }
-        if can_assign && matches!(self.current, Some(Token::Equal { .. })) {
+        if can_assign
+            && let Some(tk) = &self.current
+            && (matches!(tk, Token::Equal { .. }) || Self::compound_op(tk).is_some())
+        {
             self.advance();
             return error::InvalidAssignTargetSnafu {
                 token: unsafe { self.previous.clone().unwrap_unchecked() },
@@ -385,13 +534,18 @@ where
         };
 
         match cur {
-            Token::Fun { .. } => {
+            // `fun (` starts a lambda expression statement
+            Token::Fun { .. } if matches!(self.peeks.peek(), Some(Token::Identifier { .. })) => {
                 self.advance();
                 self.fun_declaration()?;
             },
             Token::Var { .. } => {
                 self.advance();
-                self.var_declaration()?;
+                self.var_declaration(false)?;
+            },
+            Token::Const { .. } => {
+                self.advance();
+                self.var_declaration(true)?;
             },
             _ => self.statement()?,
         }
@@ -410,8 +564,13 @@ where
         Ok(())
     }
 
+    /// `fun (a, b) { ... }` as an expression
+    fn lambda(&mut self, _: bool) -> Result<()> {
+        self.function(CurFunType::Fun)
+    }
+
     fn function(&mut self, ty: CurFunType) -> Result<()> {
-        let compiler = Compiler::new(ty, self);
+        Compiler::new(ty, self);
         println!("done function");
         self.begin_scope();
 
@@ -435,7 +594,15 @@ where
         }
         self.consume_right_paren()?;
         self.consume_left_brace()?;
-        self.block()?;
+        // `continue` can't jump out of the function
+        let enclosing_loop = (
+            self.innermost_loop_start.take(),
+            self.innermost_loop_depth,
+            self.innermost_loop_tries,
+        );
+        let body = self.block();
+        self.exit_loop(enclosing_loop);
+        body?;
 
         let function = self.end_compiler();
         self.emit_bytes(
@@ -474,6 +641,18 @@ where
                 self.advance();
                 self.for_statement()?;
             },
+            Token::Continue { .. } => {
+                self.advance();
+                self.continue_statement()?;
+            },
+            Token::Throw { .. } => {
+                self.advance();
+                self.throw_statement()?;
+            },
+            Token::Try { .. } => {
+                self.advance();
+                self.try_statement()?;
+            },
             _ => self.expression_statement()?,
         }
         Ok(())
@@ -494,7 +673,7 @@ where
             },
             Token::Var { .. } => {
                 self.advance();
-                self.var_declaration()?;
+                self.var_declaration(false)?;
             },
             _ => {
                 self.expression_statement()?;
@@ -531,7 +710,10 @@ where
             Self::patch_jump(body_jump)?;
         }
 
-        self.statement()?;
+        let enclosing = self.enter_loop(loop_start);
+        let body = self.statement();
+        self.exit_loop(enclosing);
+        body?;
 
         self.emit_loop(loop_start)?;
 
@@ -553,7 +735,10 @@ where
 
         let exit_jump = self.emit_jump(OpCode::OpJumpIfFalse);
         self.emit_byte(OpCode::OpPop);
-        self.statement()?;
+        let enclosing = self.enter_loop(loop_start);
+        let body = self.statement();
+        self.exit_loop(enclosing);
+        body?;
 
         self.emit_loop(loop_start)?;
 
@@ -563,6 +748,62 @@ where
         Ok(())
     }
 
+    /// Returns the enclosing loop, give it back to [`Self::exit_loop`]
+    fn enter_loop(&mut self, loop_start: usize) -> (Option<usize>, usize, usize) {
+        let depth = self.cur_compiler().map_or(0, |v| v.scope_depth);
+        let enclosing = (
+            self.innermost_loop_start,
+            self.innermost_loop_depth,
+            self.innermost_loop_tries,
+        );
+        self.innermost_loop_start = Some(loop_start);
+        self.innermost_loop_depth = depth;
+        self.innermost_loop_tries = self.try_locals.len();
+        enclosing
+    }
+
+    const fn exit_loop(&mut self, (start, depth, tries): (Option<usize>, usize, usize)) {
+        self.innermost_loop_start = start;
+        self.innermost_loop_depth = depth;
+        self.innermost_loop_tries = tries;
+    }
+
+    fn continue_statement(&mut self) -> Result<()> {
+        let Some(loop_start) = self.innermost_loop_start
+        else {
+            return error::NotMatchSnafu {
+                msg: "Can't use 'continue' outside of a loop",
+                token: self.previous.clone(),
+            }
+            .fail();
+        };
+        self.consume_semicolon()?;
+
+        // run the `finally` of the `try` statements inside the loop, unwinding drops their locals
+        let levels = self.try_locals.len() - self.innermost_loop_tries;
+        if levels > 0 {
+            let Ok(levels) = u8::try_from(levels)
+            else {
+                return error::TooMuchJumpSnafu.fail();
+            };
+            self.emit_bytes(OpCode::OpUnwind, levels);
+        }
+        let kept = self.try_locals.get(self.innermost_loop_tries).copied();
+
+        // discard the locals of the loop body, they stay declared for the rest of the body
+        if let Some(compiler) = self.cur_compiler() {
+            let pops = compiler.locals[..kept.unwrap_or(compiler.locals.len())]
+                .iter()
+                .rev()
+                .take_while(|local| local.depth as usize > self.innermost_loop_depth)
+                .count();
+            for _ in 0..pops {
+                self.emit_byte(OpCode::OpPop);
+            }
+        }
+        self.emit_loop(loop_start)
+    }
+
     fn emit_loop(&self, loop_start: usize) -> Result<()> {
         self.emit_byte(OpCode::OpLoop);
 
@@ -613,7 +854,96 @@ where
             v.code[offset] = ((jump >> 8) & 0xFF) as u8;
         });
         CUR_CHUNK.with_borrow_mut(|v| {
-            v.code[offset + 1] = ((jump >> 8) & 0xFF) as u8;
+            v.code[offset + 1] = (jump & 0xFF) as u8;
+        });
+        Ok(())
+    }
+
+    fn throw_statement(&mut self) -> Result<()> {
+        self.expression()?;
+        self.consume_semicolon()?;
+        self.emit_byte(OpCode::OpThrow);
+        Ok(())
+    }
+
+    /// `OpTry body OpEndTry [catch OpEndTry] finally OpEndFinally`, the handler pushed by `OpTry`
+    /// stays active through the `catch` clause
+    fn try_statement(&mut self) -> Result<()> {
+        let keyword = self.previous.clone().unwrap();
+        self.emit_byte(OpCode::OpTry);
+        // a catch offset of 0 until patched means no `catch` clause
+        let catch_offset = CUR_CHUNK.with_borrow(Chunk::count);
+        self.emit_bytes(0, 0);
+        let finally_offset = catch_offset + 2;
+        self.emit_bytes(0xFF, 0xFF);
+        let base = finally_offset + 2;
+
+        let locals = self.cur_compiler().map_or(0, |v| v.locals.len());
+        self.try_locals.push(locals);
+        let handled = self.try_handled(catch_offset, base);
+        self.try_locals.pop();
+        let has_catch = handled?;
+
+        Self::patch_try(finally_offset, base)?;
+        if matches!(self.current, Some(Token::Finally { .. })) {
+            self.advance();
+            self.try_body()?;
+        }
+        else if !has_catch {
+            return error::TryHandlerSnafu { keyword }.fail();
+        }
+        self.emit_byte(OpCode::OpEndFinally);
+
+        Ok(())
+    }
+
+    /// The body and `catch` clause guarded by the handler, returns whether there is a `catch`
+    fn try_handled(&mut self, catch_offset: usize, base: usize) -> Result<bool> {
+        self.try_body()?;
+        self.emit_byte(OpCode::OpEndTry);
+        if !matches!(self.current, Some(Token::Catch { .. })) {
+            return Ok(false);
+        }
+        self.advance();
+        Self::patch_try(catch_offset, base)?;
+        self.catch_clause()?;
+        self.emit_byte(OpCode::OpEndTry);
+        Ok(true)
+    }
+
+    fn try_body(&mut self) -> Result<()> {
+        self.consume_left_brace()?;
+        self.begin_scope();
+        self.block()?;
+        self.end_scope();
+        Ok(())
+    }
+
+    /// `catch (name) { .. }`, the thrown value is already on the stack as the local `name`
+    fn catch_clause(&mut self) -> Result<()> {
+        self.consume_left_paren()?;
+        self.consume_ident()?;
+        let name = self.previous.clone().unwrap();
+        self.consume_right_paren()?;
+        self.consume_left_brace()?;
+
+        self.begin_scope();
+        self.add_local(name)?;
+        self.mark_initialized();
+        self.block()?;
+        self.end_scope();
+        Ok(())
+    }
+
+    /// Points the `OpTry` operand at `offset` to the current end of the chunk
+    fn patch_try(offset: usize, base: usize) -> Result<()> {
+        let jump = CUR_CHUNK.with_borrow(Chunk::count) - base;
+        let Ok(jump) = u16::try_from(jump)
+        else {
+            return error::TooMuchJumpSnafu.fail();
+        };
+        CUR_CHUNK.with_borrow_mut(|v| {
+            v.code[offset..offset + 2].copy_from_slice(&jump.to_be_bytes());
         });
         Ok(())
     }
@@ -655,8 +985,10 @@ where
         Ok(())
     }
 
-    fn var_declaration(&mut self) -> Result<()> {
+    /// `var name = expr;`, or `const name = expr;` when `is_const`
+    fn var_declaration(&mut self, is_const: bool) -> Result<()> {
         let global = self.parse_variable()?;
+        let name = unsafe { self.previous.clone().unwrap_unchecked() };
         let Some(cur) = &self.current
         else {
             return error::MissingCurSnafu.fail();
@@ -667,8 +999,19 @@ where
                 self.advance();
                 self.expression()?;
             },
+            _ if is_const => return error::ConstInitSnafu { name }.fail(),
             _ => self.emit_byte(OpCode::OpNil),
         }
+        if is_const {
+            match self.cur_compiler_mut() {
+                Some(compiler) if compiler.scope_depth > 0 => {
+                    unsafe { compiler.locals.last_mut().unwrap_unchecked() }.is_const = true;
+                },
+                _ => {
+                    self.const_globals.insert(name.lexeme().to_owned());
+                },
+            }
+        }
         self.consume_semicolon()?;
 
         self.define_var_global(global);
@@ -695,13 +1038,17 @@ where
     }
 
     fn declare_var(&mut self) -> Result<()> {
-        if self.cur_compiler().map_or(0, |v| v.scope_depth) == 0 {
-            return Ok(());
-        }
         let Some(name) = self.previous.clone()
         else {
             return error::MissingPrevSnafu.fail();
         };
+        if self.cur_compiler().map_or(0, |v| v.scope_depth) == 0 {
+            // a global const can't be redeclared
+            if self.const_globals.contains(name.lexeme()) {
+                return error::DuplicateVarNameSnafu { name }.fail();
+            }
+            return Ok(());
+        }
         for local in self.cur_compiler().unwrap().locals.iter().rev() {
             if local.depth != -1
                 && (local.depth as usize) < self.cur_compiler().unwrap().scope_depth
@@ -722,10 +1069,11 @@ where
         if self.cur_compiler().unwrap().locals.len() == u8::MAX.into() {
             return error::TooManyLocalVarSnafu.fail();
         }
-        self.cur_compiler_mut()
-            .unwrap()
-            .locals
-            .push(Local { name, depth: -1 });
+        self.cur_compiler_mut().unwrap().locals.push(Local {
+            name,
+            depth: -1,
+            is_const: false,
+        });
 
         Ok(())
     }
@@ -762,6 +1110,20 @@ where
         self.parse_precedence(Precedence::Or)?;
         Self::patch_jump(end_jump)
     }
+
+    fn conditional(&mut self, _: bool) -> Result<()> {
+        let then_jump = self.emit_jump(OpCode::OpJumpIfFalse);
+        self.emit_byte(OpCode::OpPop);
+        self.expression()?;
+        let else_jump = self.emit_jump(OpCode::OpJump);
+
+        Self::patch_jump(then_jump)?;
+        self.emit_byte(OpCode::OpPop);
+        self.consume_colon()?;
+        // same precedence, so `a ? b : c ? d : e` nests to the right
+        self.parse_precedence(Precedence::Conditional)?;
+        Self::patch_jump(else_jump)
+    }
 }
 
 impl<I> Parser<I>
@@ -780,12 +1142,21 @@ where
             had_error: false,
             panic_mode: false,
             cur_compiler: None,
+            innermost_loop_start: None,
+            innermost_loop_depth: 0,
+            innermost_loop_tries: 0,
+            try_locals: vec![],
+            const_globals: HashSet::new(),
         }
     }
     fn cur_compiler(&self) -> Option<&Compiler> {
         self.cur_compiler.map(|v| unsafe { &(*v.as_ptr()) })
     }
 
+    #[expect(
+        clippy::needless_pass_by_ref_mut,
+        reason = "mutate through the raw pointer"
+    )]
     fn cur_compiler_mut(&mut self) -> Option<&mut Compiler> {
         self.cur_compiler.map(|v| unsafe { &mut (*v.as_ptr()) })
     }
@@ -820,6 +1191,7 @@ where
         }
     }
 
+    #[expect(dead_code, reason = "keep it for later error reporting")]
     fn error(&mut self) {
         let token = self.previous.clone();
         self.error_at(token);
@@ -836,10 +1208,13 @@ where
                 Token::Class { .. }
                 | Token::Fun { .. }
                 | Token::Var { .. }
+                | Token::Const { .. }
                 | Token::For { .. }
                 | Token::If { .. }
                 | Token::While { .. }
                 | Token::Print { .. }
+                | Token::Throw { .. }
+                | Token::Try { .. }
                 | Token::Return { .. } => return,
                 _ => {},
             };
@@ -901,6 +1276,24 @@ where
         }
     }
 
+    fn consume_colon(&mut self) -> Result<()> {
+        let Some(tk) = &self.current
+        else {
+            return error::MissingCurSnafu.fail();
+        };
+        match tk {
+            Token::Colon { .. } => {
+                self.advance();
+                Ok(())
+            },
+            t => error::NotMatchSnafu {
+                msg: "Expect ':' after then branch of conditional expression",
+                token: Some(t.clone()),
+            }
+            .fail(),
+        }
+    }
+
     fn consume_right_paren(&mut self) -> Result<()> {
         let Some(tk) = &self.current
         else {
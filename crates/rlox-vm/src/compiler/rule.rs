@@ -40,16 +40,26 @@ where
             infix: None,
             precedence: Precedence::None,
         },
-        Token::Comma { .. } => ParseRule {
+        Token::LeftBracket { .. } => ParseRule {
             prefix: None,
             infix: None,
             precedence: Precedence::None,
         },
-        Token::Dot { .. } => ParseRule {
+        Token::RightBracket { .. } => ParseRule {
+            prefix: None,
+            infix: None,
+            precedence: Precedence::None,
+        },
+        Token::Comma { .. } => ParseRule {
             prefix: None,
             infix: None,
             precedence: Precedence::None,
         },
+        Token::Dot { .. } => ParseRule {
+            prefix: None,
+            infix: Some(Parser::dot),
+            precedence: Precedence::Call,
+        },
         Token::Minus { .. } => ParseRule {
             prefix: Some(Parser::unary),
             infix: Some(Parser::binary),
@@ -75,6 +85,26 @@ where
             infix: Some(Parser::binary),
             precedence: Precedence::Factor,
         },
+        Token::Percent { .. } => ParseRule {
+            prefix: None,
+            infix: Some(Parser::binary),
+            precedence: Precedence::Factor,
+        },
+        Token::StarStar { .. } => ParseRule {
+            prefix: None,
+            infix: Some(Parser::binary),
+            precedence: Precedence::Power,
+        },
+        Token::Question { .. } => ParseRule {
+            prefix: None,
+            infix: Some(Parser::conditional),
+            precedence: Precedence::Conditional,
+        },
+        Token::Colon { .. } => ParseRule {
+            prefix: None,
+            infix: None,
+            precedence: Precedence::None,
+        },
         Token::Bang { .. } => ParseRule {
             prefix: Some(Parser::unary),
             infix: None,
@@ -95,6 +125,30 @@ where
             infix: Some(Parser::binary),
             precedence: Precedence::Equality,
         },
+        Token::FatArrow { .. } => ParseRule {
+            prefix: None,
+            infix: None,
+            precedence: Precedence::None,
+        },
+        Token::DotDotDot { .. } => ParseRule {
+            prefix: None,
+            infix: None,
+            precedence: Precedence::None,
+        },
+        Token::PlusEqual { .. }
+        | Token::MinusEqual { .. }
+        | Token::StarEqual { .. }
+        | Token::SlashEqual { .. }
+        | Token::PercentEqual { .. } => ParseRule {
+            prefix: None,
+            infix: None,
+            precedence: Precedence::None,
+        },
+        Token::PlusPlus { .. } | Token::MinusMinus { .. } => ParseRule {
+            prefix: Some(Parser::prefix_increment),
+            infix: None,
+            precedence: Precedence::None,
+        },
         Token::Greater { .. } => ParseRule {
             prefix: None,
             infix: Some(Parser::binary),
@@ -125,6 +179,11 @@ where
             infix: None,
             precedence: Precedence::None,
         },
+        Token::Interpolation { .. } => ParseRule {
+            prefix: Some(Parser::interpolation),
+            infix: None,
+            precedence: Precedence::None,
+        },
         Token::Number { .. } => ParseRule {
             prefix: Some(Parser::number),
             infix: None,
@@ -146,7 +205,7 @@ where
             precedence: Precedence::None,
         },
         Token::Fun { .. } => ParseRule {
-            prefix: None,
+            prefix: Some(Parser::lambda),
             infix: None,
             precedence: Precedence::None,
         },
@@ -230,6 +289,71 @@ where
             infix: None,
             precedence: Precedence::None,
         },
+        Token::Continue { .. } => ParseRule {
+            prefix: None,
+            infix: None,
+            precedence: Precedence::None,
+        },
+        Token::Match { .. } => ParseRule {
+            prefix: None,
+            infix: None,
+            precedence: Precedence::None,
+        },
+        Token::In { .. } => ParseRule {
+            prefix: None,
+            infix: None,
+            precedence: Precedence::None,
+        },
+        Token::Trait { .. } => ParseRule {
+            prefix: None,
+            infix: None,
+            precedence: Precedence::None,
+        },
+        Token::With { .. } => ParseRule {
+            prefix: None,
+            infix: None,
+            precedence: Precedence::None,
+        },
+        Token::Const { .. } => ParseRule {
+            prefix: None,
+            infix: None,
+            precedence: Precedence::None,
+        },
+        Token::Import { .. } => ParseRule {
+            prefix: None,
+            infix: None,
+            precedence: Precedence::None,
+        },
+        Token::As { .. } => ParseRule {
+            prefix: None,
+            infix: None,
+            precedence: Precedence::None,
+        },
+        Token::Throw { .. } => ParseRule {
+            prefix: None,
+            infix: None,
+            precedence: Precedence::None,
+        },
+        Token::Try { .. } => ParseRule {
+            prefix: None,
+            infix: None,
+            precedence: Precedence::None,
+        },
+        Token::Catch { .. } => ParseRule {
+            prefix: None,
+            infix: None,
+            precedence: Precedence::None,
+        },
+        Token::Finally { .. } => ParseRule {
+            prefix: None,
+            infix: None,
+            precedence: Precedence::None,
+        },
+        Token::Yield { .. } => ParseRule {
+            prefix: None,
+            infix: None,
+            precedence: Precedence::None,
+        },
         Token::Invalid { .. } => ParseRule {
             prefix: None,
             infix: None,
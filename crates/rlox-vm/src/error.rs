@@ -25,6 +25,12 @@ pub enum LoxError {
         #[snafu(implicit)]
         localtion: Location,
     },
+    #[snafu(display("Scan error, found {count} problems"))]
+    Scan {
+        count: usize,
+        #[snafu(implicit)]
+        localtion: Location,
+    },
     #[snafu(display("Compile error"))]
     CompileError {
         #[snafu(implicit)]
@@ -54,7 +60,8 @@ pub enum LoxError {
     TooManyConsts {
         #[snafu(implicit)]
         localtion: Location,
-    },#[snafu(display("Too many local variables"))]
+    },
+    #[snafu(display("Too many local variables"))]
     TooManyLocalVar {
         #[snafu(implicit)]
         localtion: Location,
@@ -104,6 +111,26 @@ pub enum LoxError {
         #[snafu(implicit)]
         localtion: Location,
     },
+    #[snafu(display("Undefined property {name}: line: {line}"))]
+    NoProperty {
+        name: String,
+        line: usize,
+        #[snafu(implicit)]
+        localtion: Location,
+    },
+    #[snafu(display("Uncaught exception {value}: line: {line}"))]
+    Uncaught {
+        value: String,
+        line: usize,
+        #[snafu(implicit)]
+        localtion: Location,
+    },
+    #[snafu(display("Expect `catch` or `finally` after try block: {}", keyword))]
+    TryHandler {
+        keyword: Token,
+        #[snafu(implicit)]
+        localtion: Location,
+    },
     #[snafu(display("Invalid assignment target: {}", token))]
     InvalidAssignTarget {
         token: Token,
@@ -116,6 +143,18 @@ pub enum LoxError {
         #[snafu(implicit)]
         localtion: Location,
     },
+    #[snafu(display("Can't assign to const variable: {}", name))]
+    AssignConst {
+        name: Token,
+        #[snafu(implicit)]
+        localtion: Location,
+    },
+    #[snafu(display("Expect `=` and a value after const name: {}", name))]
+    ConstInit {
+        name: Token,
+        #[snafu(implicit)]
+        localtion: Location,
+    },
     #[snafu(display("Can't read local variable in its own initializer: {}", name))]
     OwnInit {
         name: Token,
@@ -139,4 +178,19 @@ pub enum LoxError {
     },
 }
 
+impl LoxError {
+    /// Runtime errors a `catch` clause can handle, caught as an error with `message` and `line`.
+    ///
+    /// A broken stack or handler table is a bug of the VM, not for the program to catch
+    pub const fn is_catchable(&self) -> bool {
+        matches!(
+            self,
+            Self::NegateNotNum { .. }
+                | Self::BinaryNotNum { .. }
+                | Self::UndefindVar { .. }
+                | Self::NoProperty { .. }
+        )
+    }
+}
+
 pub type Result<T> = std::result::Result<T, LoxError>;
@@ -1,5 +1,3 @@
-#![feature(let_chains, iter_next_chunk)]
-
 pub mod chunk;
 pub mod cli;
 pub mod compiler;
@@ -1,6 +1,6 @@
 use std::fmt::Display;
 
-use crate::chunk::Chunk;
+use crate::{chunk::Chunk, value::Value};
 
 #[derive(Clone)]
 #[derive(Debug)]
@@ -8,6 +8,7 @@ use crate::chunk::Chunk;
 pub enum Obj {
     String(String),
     Fun(ObjFunction),
+    Error(ObjError),
 }
 
 impl Display for Obj {
@@ -15,6 +16,28 @@ impl Display for Obj {
         match self {
             Self::String(s) => s.fmt(f),
             Self::Fun(fun) => write!(f, "<fn {}>", fun.name),
+            Self::Error(_) => f.write_str("Error instance"),
+        }
+    }
+}
+
+/// A runtime error caught by `catch`, with `message` and `line` properties
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq, PartialOrd)]
+pub struct ObjError {
+    pub message: String,
+    pub line: usize,
+}
+
+impl ObjError {
+    /// The `message` or `line` property
+    pub fn get(&self, name: &str) -> Option<Value> {
+        match name {
+            "message" => Some(Value::Obj(Obj::String(self.message.clone()))),
+            #[expect(clippy::cast_precision_loss, reason = "line numbers are small")]
+            "line" => Some(Value::Number(self.line as f64)),
+            _ => None,
         }
     }
 }
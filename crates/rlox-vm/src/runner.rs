@@ -41,7 +41,7 @@ pub fn run_file<P: AsRef<Path>>(vm: &mut Vm, path: P) -> Result<()> {
     })?;
     let result = vm.interpret(&source);
     match result {
-        Err(LoxError::CompileError { .. }) => exit(65),
+        Err(LoxError::CompileError { .. } | LoxError::Scan { .. }) => exit(65),
         Err(LoxError::RuntimeError { .. }) => exit(70),
         v => v,
     }
@@ -106,3 +106,295 @@ print breakfast;
     )
     .unwrap();
 }
+
+#[test]
+fn const_binding() {
+    use crate::chunk::OpCode;
+
+    // the shadowing `limit` is assigned as a local, the global const is only read
+    let chunk = compiled(
+        r#"
+const limit = 3;
+{
+    const step = limit + 1;
+    var limit = step;
+    limit = limit + 1;
+    print limit;
+}
+print limit;
+        "#,
+    );
+    assert_eq!(
+        chunk.opcodes(),
+        [
+            OpCode::OpConstant,
+            OpCode::OpDefaineGlobal,
+            OpCode::OpGetGlobal,
+            OpCode::OpConstant,
+            OpCode::OpAdd,
+            OpCode::OpGetLocal,
+            OpCode::OpGetLocal,
+            OpCode::OpConstant,
+            OpCode::OpAdd,
+            OpCode::OpSetLocal,
+            OpCode::OpPop,
+            OpCode::OpGetLocal,
+            OpCode::OpPrint,
+            OpCode::OpPop,
+            OpCode::OpPop,
+            OpCode::OpGetGlobal,
+            OpCode::OpPrint,
+            OpCode::OpReturn,
+        ]
+    );
+
+    for source in [
+        "const a = 1; a = 2;",
+        "const a = 1; a += 2;",
+        "{ const b = 1; b++; }",
+        "{ const b = 1; --b; }",
+        "const a = 1; var a = 2;",
+        "const a;",
+    ] {
+        let mut vm = Vm::new();
+        assert!(vm.interpret(source).is_err(), "{source}");
+    }
+}
+
+/// What `source` compiles to, `interpret` doesn't run it yet
+#[cfg(test)]
+fn compiled(source: &str) -> crate::chunk::Chunk {
+    use rlox::scan::scanner::Scanner;
+
+    use crate::compiler::{CUR_CHUNK, Parser};
+
+    CUR_CHUNK.take();
+    let tokens: Vec<_> = Scanner::new(source).scan_tokens().collect();
+    Parser::new(tokens).compile().unwrap();
+    CUR_CHUNK.take()
+}
+
+#[test]
+fn try_catch() {
+    use crate::{
+        chunk::{Chunk, OpCode},
+        object::{Obj, ObjFunction},
+        value::Value,
+        vm::CallFrame,
+    };
+
+    let chunk = compiled(
+        r#"
+try {
+    try {
+        print -"x";
+    } finally {
+        print "inner";
+    }
+} catch (e) {
+    print e.message;
+}
+        "#,
+    );
+    assert_eq!(
+        chunk.opcodes(),
+        [
+            OpCode::OpTry,
+            OpCode::OpTry,
+            OpCode::OpConstant,
+            OpCode::OpNegate,
+            OpCode::OpPrint,
+            OpCode::OpEndTry,
+            OpCode::OpConstant,
+            OpCode::OpPrint,
+            OpCode::OpEndFinally,
+            OpCode::OpEndTry,
+            OpCode::OpGetLocal,
+            OpCode::OpGetProperty,
+            OpCode::OpPrint,
+            OpCode::OpPop,
+            OpCode::OpEndTry,
+            OpCode::OpEndFinally,
+            OpCode::OpReturn,
+        ]
+    );
+
+    for source in [
+        "try {}",
+        "try {} print 1;",
+        "try {} catch (1) {}",
+        "throw;",
+        "try {} catch (e) { e.line = 1; }",
+    ] {
+        let mut vm = Vm::new();
+        assert!(vm.interpret(source).is_err(), "{source}");
+    }
+
+    // `interpret` doesn't run the compiled chunk yet, so this is
+    // `try { throw "boom"; } catch (e) { caught = e; } finally { done = true; }` and
+    // `try { try { throw "up"; } finally { inner = true; } } catch (e) { outer = e; }`,
+    // assembled by hand
+    let mut chunk = Chunk::new();
+    let mut string = |s: &str| chunk.add_constant(Value::Obj(Obj::String(s.to_owned()))) as u8;
+    let (boom, caught, done, up, inner, outer) = (
+        string("boom"),
+        string("caught"),
+        string("done"),
+        string("up"),
+        string("inner"),
+        string("outer"),
+    );
+    #[rustfmt::skip]
+    let code: [u8; 40] = [
+        OpCode::OpTry.into(), 0, 4, 0, 7,
+        OpCode::OpConstant.into(), boom,
+        OpCode::OpThrow.into(),
+        OpCode::OpEndTry.into(),
+        OpCode::OpDefaineGlobal.into(), caught,
+        OpCode::OpEndTry.into(),
+        OpCode::OpTrue.into(),
+        OpCode::OpDefaineGlobal.into(), done,
+        OpCode::OpEndFinally.into(),
+
+        OpCode::OpTry.into(), 0, 14, 0, 17,
+        OpCode::OpTry.into(), 0, 0, 0, 4,
+        OpCode::OpConstant.into(), up,
+        OpCode::OpThrow.into(),
+        OpCode::OpEndTry.into(),
+        OpCode::OpTrue.into(),
+        OpCode::OpDefaineGlobal.into(), inner,
+        OpCode::OpEndFinally.into(),
+        OpCode::OpEndTry.into(),
+        OpCode::OpDefaineGlobal.into(), outer,
+        OpCode::OpEndTry.into(),
+        OpCode::OpEndFinally.into(),
+        OpCode::OpReturn.into(),
+    ];
+    for byte in code {
+        chunk.write(byte, 1);
+    }
+
+    let mut vm = Vm::new();
+    vm.frames.push(CallFrame::new(
+        ObjFunction {
+            chunk,
+            ..ObjFunction::new()
+        },
+        vec![],
+    ));
+    vm.run().unwrap();
+    assert_eq!(
+        vm.globals["caught"],
+        Value::Obj(Obj::String("boom".to_owned()))
+    );
+    assert_eq!(vm.globals["done"], Value::Bool(true));
+    assert_eq!(vm.globals["inner"], Value::Bool(true));
+    assert_eq!(
+        vm.globals["outer"],
+        Value::Obj(Obj::String("up".to_owned()))
+    );
+}
+
+#[test]
+fn catch_runtime_error() {
+    use crate::{
+        chunk::{Chunk, OpCode},
+        object::{Obj, ObjFunction},
+        value::Value,
+        vm::CallFrame,
+    };
+
+    // `interpret` doesn't run the compiled chunk yet, so this is
+    // `try { -"x"; } catch (e) { message = e.message; }` on line 1 and
+    // `try { nope; } catch (e) { line = e.line; }` on line 2, assembled by hand
+    let mut chunk = Chunk::new();
+    let mut string = |s: &str| chunk.add_constant(Value::Obj(Obj::String(s.to_owned()))) as u8;
+    let (x, message, nope, line) = (
+        string("x"),
+        string("message"),
+        string("nope"),
+        string("line"),
+    );
+    #[rustfmt::skip]
+    let code: [(&[u8], usize); 2] = [
+        (&[
+            OpCode::OpTry.into(), 0, 5, 0, 10,
+            OpCode::OpConstant.into(), x,
+            OpCode::OpNegate.into(),
+            OpCode::OpPop.into(),
+            OpCode::OpEndTry.into(),
+            OpCode::OpGetProperty.into(), message,
+            OpCode::OpDefaineGlobal.into(), message,
+            OpCode::OpEndTry.into(),
+            OpCode::OpEndFinally.into(),
+        ], 1),
+        (&[
+            OpCode::OpTry.into(), 0, 4, 0, 9,
+            OpCode::OpGetGlobal.into(), nope,
+            OpCode::OpPop.into(),
+            OpCode::OpEndTry.into(),
+            OpCode::OpGetProperty.into(), line,
+            OpCode::OpDefaineGlobal.into(), line,
+            OpCode::OpEndTry.into(),
+            OpCode::OpEndFinally.into(),
+            OpCode::OpReturn.into(),
+        ], 2),
+    ];
+    for (bytes, line) in code {
+        for &byte in bytes {
+            chunk.write(byte, line);
+        }
+    }
+
+    let mut vm = Vm::new();
+    vm.frames.push(CallFrame::new(
+        ObjFunction {
+            chunk,
+            ..ObjFunction::new()
+        },
+        vec![],
+    ));
+    vm.run().unwrap();
+    assert_eq!(
+        vm.globals["message"],
+        Value::Obj(Obj::String(
+            "Negate operand must be a number: line: 1".to_owned()
+        ))
+    );
+    assert_eq!(vm.globals["line"], Value::Number(2.0));
+}
+
+#[test]
+fn uncaught_vm_error() {
+    use crate::{
+        chunk::{Chunk, OpCode},
+        object::ObjFunction,
+        vm::CallFrame,
+    };
+
+    // `try { <pop the empty stack> } catch (e) {}`, a broken stack is a bug of the VM
+    let mut chunk = Chunk::new();
+    #[rustfmt::skip]
+    let code: [u8; 11] = [
+        OpCode::OpTry.into(), 0, 2, 0, 4,
+        OpCode::OpPop.into(),
+        OpCode::OpEndTry.into(),
+        OpCode::OpPop.into(),
+        OpCode::OpEndTry.into(),
+        OpCode::OpEndFinally.into(),
+        OpCode::OpReturn.into(),
+    ];
+    for byte in code {
+        chunk.write(byte, 1);
+    }
+
+    let mut vm = Vm::new();
+    vm.frames.push(CallFrame::new(
+        ObjFunction {
+            chunk,
+            ..ObjFunction::new()
+        },
+        vec![],
+    ));
+    assert!(matches!(vm.run(), Err(LoxError::EmptyStack { .. })));
+}
@@ -6,7 +6,7 @@ use crate::{
     chunk::{Chunk, OpCode},
     compiler::Parser,
     error::{self, Result},
-    object::{Obj, ObjFunction},
+    object::{Obj, ObjError, ObjFunction},
     value::Value,
 };
 
@@ -31,6 +31,34 @@ pub struct CallFrame {
     ip: usize,
     ip_code: Vec<u8>,
     slots: Vec<Value>,
+    /// Active `try` statements, innermost last
+    handlers: Vec<Handler>,
+    /// Why each running `finally` was entered, innermost last
+    completions: Vec<Completion>,
+}
+
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct Handler {
+    /// Taken once the handler catches, a throw from the `catch` clause goes to `finally`
+    catch_ip: Option<usize>,
+    finally_ip: usize,
+    /// Stack height at `try`, the values above it are dropped when unwinding
+    stack_len: usize,
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, PartialOrd)]
+enum Completion {
+    Normal,
+    Throw(Value),
+    /// A `continue` leaving `levels` more handlers before going on at `resume`
+    Unwind {
+        levels: u8,
+        resume: usize,
+    },
 }
 
 impl Vm {
@@ -47,23 +75,44 @@ impl Vm {
 
     pub fn interpret(&mut self, source: &str) -> Result<()> {
         let mut scanner = Scanner::new(source);
-        let p = Parser::new(scanner.scan_tokens());
+        let tokens: Vec<_> = scanner.scan_tokens().collect();
+        if !scanner.errors().is_empty() {
+            for e in scanner.errors() {
+                tracing::error!("{e}");
+            }
+            return error::ScanSnafu {
+                count: scanner.errors().len(),
+            }
+            .fail();
+        }
+        let p = Parser::new(tokens);
         let function = p.compile()?;
         self.stack.push(Value::Obj(Obj::Fun(function.clone())));
-        self.frames.push(CallFrame {
-            function: function.clone(),
-            ip: 0,
-            slots: self.stack.clone(),
-            ip_code: function.chunk.code,
-        });
+        self.frames
+            .push(CallFrame::new(function, self.stack.clone()));
         self.run()
     }
 
-    #[expect(clippy::unwrap_in_result, reason = "lazy")]
     pub fn run(&mut self) -> Result<()> {
         #[expect(clippy::unwrap_used, reason = "lazy")]
         let mut frame = self.frames.last().unwrap().clone();
+        loop {
+            match self.run_frame(&mut frame) {
+                Err(e) if e.is_catchable() && !frame.handlers.is_empty() => {
+                    let value = Value::Obj(Obj::Error(ObjError {
+                        message: e.to_string(),
+                        line: frame.function.chunk.get_line(frame.ip.saturating_sub(1)),
+                    }));
+                    frame.throw(&mut self.stack, value)?;
+                },
+                res => return res,
+            }
+        }
+    }
 
+    /// Runs until the end of the frame, a catchable error leaves `frame.ip` after the failed
+    /// instruction
+    fn run_frame(&mut self, frame: &mut CallFrame) -> Result<()> {
         macro_rules! binary_op {
             ($op:tt, $offset:expr, $type:ident) => {
                 {
@@ -89,10 +138,7 @@ impl Vm {
                     print!("[{}]", ele);
                 }
                 println!();
-                Chunk::disassemble_instruction(
-                    &frame.function.chunk,
-                    frame.ip - frame.function.chunk.code.len(),
-                );
+                Chunk::disassemble_instruction(&frame.function.chunk, frame.ip);
             };
 
             match frame.read_byte().into() {
@@ -123,6 +169,15 @@ impl Vm {
                         },
                     }
                 },
+                OpCode::OpToString => {
+                    let Some(value) = self.stack.last_mut()
+                    else {
+                        return error::EmptyStackSnafu.fail();
+                    };
+                    if !matches!(value, Value::Obj(Obj::String(_))) {
+                        *value = Value::Obj(Obj::String(value.to_string()));
+                    }
+                },
                 OpCode::OpNil => self.stack.push(Value::Nil),
                 OpCode::OpTrue => self.stack.push(Value::Bool(true)),
                 OpCode::OpFalse => self.stack.push(Value::Bool(false)),
@@ -180,6 +235,35 @@ impl Vm {
                 OpCode::OpSubtract => binary_op!(-, self.ip, Number),
                 OpCode::OpMultiply => binary_op!(*, self.ip, Number),
                 OpCode::OpDivide => binary_op!(/, self.ip, Number),
+                OpCode::OpModulo => binary_op!(%, self.ip, Number),
+                OpCode::OpPower => {
+                    let last: Option<&[Value; 2]> = self.stack.last_chunk();
+                    match last {
+                        Some([Value::Number(a), Value::Number(b)]) => {
+                            self.stack.push(Value::Number(a.powf(*b)));
+                        },
+                        _ => {
+                            return error::BinaryNotNumSnafu {
+                                line: frame.function.chunk.get_line(frame.ip),
+                            }
+                            .fail();
+                        },
+                    }
+                },
+                OpCode::OpGetProperty => {
+                    let name = frame.read_string();
+                    let line = frame.function.chunk.get_line(frame.ip - 1);
+                    let value = match self.stack.pop() {
+                        Some(Value::Obj(Obj::Error(error))) => error.get(&name),
+                        Some(_) => None,
+                        None => return error::EmptyStackSnafu.fail(),
+                    };
+                    let Some(value) = value
+                    else {
+                        return error::NoPropertySnafu { name, line }.fail();
+                    };
+                    self.stack.push(value);
+                },
                 OpCode::OpEqual => {
                     let Some(b) = self.stack.pop()
                     else {
@@ -223,6 +307,43 @@ impl Vm {
                     let offset = frame.read_short();
                     frame.ip -= offset as usize;
                 },
+                OpCode::OpTry => {
+                    let catch = frame.read_short() as usize;
+                    let finally = frame.read_short() as usize;
+                    frame.handlers.push(Handler {
+                        catch_ip: (catch != 0).then_some(frame.ip + catch),
+                        finally_ip: frame.ip + finally,
+                        stack_len: self.stack.len(),
+                    });
+                },
+                OpCode::OpEndTry => {
+                    let Some(handler) = frame.handlers.pop()
+                    else {
+                        return error::RuntimeSnafu.fail();
+                    };
+                    frame.completions.push(Completion::Normal);
+                    frame.ip = handler.finally_ip;
+                },
+                OpCode::OpThrow => {
+                    let Some(value) = self.stack.pop()
+                    else {
+                        return error::EmptyStackSnafu.fail();
+                    };
+                    frame.throw(&mut self.stack, value)?;
+                },
+                OpCode::OpEndFinally => match frame.completions.pop() {
+                    Some(Completion::Normal) => {},
+                    Some(Completion::Throw(value)) => frame.throw(&mut self.stack, value)?,
+                    Some(Completion::Unwind { levels, resume }) => {
+                        frame.unwind(&mut self.stack, levels, resume);
+                    },
+                    None => return error::RuntimeSnafu.fail(),
+                },
+                OpCode::OpUnwind => {
+                    let levels = frame.read_byte();
+                    let resume = frame.ip;
+                    frame.unwind(&mut self.stack, levels, resume);
+                },
             }
         }
 
@@ -239,6 +360,17 @@ impl Vm {
 }
 
 impl CallFrame {
+    pub fn new(function: ObjFunction, slots: Vec<Value>) -> Self {
+        Self {
+            ip: 0,
+            slots,
+            ip_code: function.chunk.code.clone(),
+            function,
+            handlers: vec![],
+            completions: vec![],
+        }
+    }
+
     fn read_byte(&mut self) -> u8 {
         let ip = self.ip;
         self.ip += 1;
@@ -248,7 +380,50 @@ impl CallFrame {
     fn read_short(&mut self) -> u16 {
         let offset = self.ip;
         self.ip += 2;
-        u16::from_be_bytes([self.ip_code[offset + 1], self.ip_code[offset + 2]])
+        u16::from_be_bytes([self.ip_code[offset], self.ip_code[offset + 1]])
+    }
+
+    /// Jumps to the innermost handler, its `catch` gets `value` on top of the stack
+    fn throw(&mut self, stack: &mut Vec<Value>, value: Value) -> Result<()> {
+        let Some(handler) = self.handlers.last_mut()
+        else {
+            return error::UncaughtSnafu {
+                value: value.to_string(),
+                line: self.function.chunk.get_line(self.ip),
+            }
+            .fail();
+        };
+        stack.truncate(handler.stack_len);
+        if let Some(catch_ip) = handler.catch_ip.take() {
+            stack.push(value);
+            self.ip = catch_ip;
+        }
+        else {
+            let finally_ip = handler.finally_ip;
+            self.handlers.pop();
+            self.completions.push(Completion::Throw(value));
+            self.ip = finally_ip;
+        }
+        Ok(())
+    }
+
+    /// Runs the `finally` of the next `levels` handlers, then goes on at `resume`
+    fn unwind(&mut self, stack: &mut Vec<Value>, levels: u8, resume: usize) {
+        if levels == 0 {
+            self.ip = resume;
+            return;
+        }
+        let Some(handler) = self.handlers.pop()
+        else {
+            self.ip = resume;
+            return;
+        };
+        stack.truncate(handler.stack_len);
+        self.completions.push(Completion::Unwind {
+            levels: levels - 1,
+            resume,
+        });
+        self.ip = handler.finally_ip;
     }
 
     fn read_string(&mut self) -> String {
@@ -76,11 +76,15 @@ impl AstPrinter {
 impl ExprVisitor<String> for AstPrinter {
     fn visit_assign_expr(&mut self, expr: &crate::expr::Assign) -> String {
         self.parenthesize2(
-            "=",
+            expr.operator().map_or("=", Token::lexeme),
             vec![&Obj::Str(expr.name().lexeme()), &Obj::Expr(expr.value())],
         )
     }
 
+    fn visit_assignunpack_expr(&mut self, expr: &crate::expr::AssignUnpack) -> String {
+        format!("(= {} {})", expr.pattern(), expr.value().accept(self))
+    }
+
     fn visit_binary_expr(&mut self, expr: &crate::expr::Binary) -> String {
         let exprs = [expr.left(), expr.right()];
         self.parenthesize(expr.operator().lexeme(), exprs)
@@ -96,6 +100,11 @@ impl ExprVisitor<String> for AstPrinter {
         )
     }
 
+    fn visit_conditional_expr(&mut self, expr: &crate::expr::Conditional) -> String {
+        let exprs = [expr.condition(), expr.then_branch(), expr.else_branch()];
+        self.parenthesize("?:", exprs)
+    }
+
     fn visit_get_expr(&mut self, expr: &crate::expr::Get) -> String {
         self.parenthesize2(
             ".",
@@ -107,6 +116,23 @@ impl ExprVisitor<String> for AstPrinter {
         self.parenthesize("group", [expr.expression()])
     }
 
+    fn visit_index_expr(&mut self, expr: &crate::expr::Index) -> String {
+        self.parenthesize("[]", [expr.object(), expr.index()])
+    }
+
+    fn visit_list_expr(&mut self, expr: &crate::expr::List) -> String {
+        self.parenthesize("list", expr.elements())
+    }
+
+    fn visit_map_expr(&mut self, expr: &crate::expr::Map) -> String {
+        self.parenthesize("map", expr.entries().iter().flat_map(|(k, v)| [k, v]))
+    }
+
+    fn visit_lambda_expr(&mut self, expr: &crate::expr::Lambda) -> String {
+        let params: Vec<_> = expr.function().params.iter().map(Token::lexeme).collect();
+        format!("(fun ({}))", params.join(" "))
+    }
+
     fn visit_literal_expr(&mut self, expr: &crate::expr::Literal) -> String {
         expr.value().to_string()
     }
@@ -117,7 +143,7 @@ impl ExprVisitor<String> for AstPrinter {
 
     fn visit_set_expr(&mut self, expr: &crate::expr::Set) -> String {
         self.parenthesize2(
-            "=",
+            expr.operator().map_or("=", Token::lexeme),
             [
                 &Obj::Expr(expr.object()),
                 &Obj::Str(expr.name().lexeme()),
@@ -126,6 +152,11 @@ impl ExprVisitor<String> for AstPrinter {
         )
     }
 
+    fn visit_setindex_expr(&mut self, expr: &crate::expr::SetIndex) -> String {
+        let name = format!("[]{}", expr.operator().map_or("=", Token::lexeme));
+        self.parenthesize(&name, [expr.object(), expr.index(), expr.value()])
+    }
+
     fn visit_super_expr(&mut self, expr: &crate::expr::Super) -> String {
         self.parenthesize2("super", [&Obj::Token(expr.method())])
     }
@@ -155,7 +186,7 @@ impl StmtVisitor<String> for AstPrinter {
     fn visit_var_stmt(&mut self, stmt: &Var) -> String {
         if let Some(i) = stmt.initializer() {
             self.parenthesize2(
-                "var",
+                if stmt.is_const() { "const" } else { "var" },
                 [&Obj::Token(stmt.name()), &Obj::Str("="), &Obj::Expr(i)],
             )
         }
@@ -164,6 +195,15 @@ impl StmtVisitor<String> for AstPrinter {
         }
     }
 
+    fn visit_destructure_stmt(&mut self, stmt: &Destructure) -> String {
+        format!(
+            "({} {} = {})",
+            stmt.keyword().lexeme(),
+            stmt.pattern(),
+            stmt.value().accept(self)
+        )
+    }
+
     fn visit_block_stmt(&mut self, stmt: &Block) -> String {
         let mut builder = String::new();
         builder.push_str("(block ");
@@ -194,23 +234,37 @@ impl StmtVisitor<String> for AstPrinter {
     }
 
     fn visit_while_stmt(&mut self, stmt: &While) -> String {
-        self.parenthesize2(
-            "while",
-            [&Obj::Expr(stmt.condition()), &Obj::Stmt(stmt.body())],
-        )
+        let mut parts = vec![Obj::Expr(stmt.condition()), Obj::Stmt(stmt.body())];
+        if let Some(increment) = stmt.increment() {
+            parts.push(Obj::Expr(increment));
+        }
+        self.parenthesize2("while", &parts)
     }
 
     fn visit_break_stmt(&mut self, stmt: &Break) -> String {
         stmt.token().lexeme().to_owned()
     }
 
+    fn visit_continue_stmt(&mut self, stmt: &Continue) -> String {
+        stmt.token().lexeme().to_owned()
+    }
+
     fn visit_function_stmt(&mut self, stmt: &Function) -> String {
         let mut builder = format!("(fun {} (", stmt.name.lexeme());
-        for ele in &stmt.params {
-            if ele != &stmt.params[0] {
+        for (idx, ele) in stmt.params.iter().enumerate() {
+            if idx > 0 {
                 builder.push(' ');
             }
             builder.push_str(ele.lexeme());
+            if let Some(default) = stmt.default_of(idx) {
+                _ = write!(&mut builder, "={}", default.accept(self));
+            }
+        }
+        if let Some(rest) = &stmt.rest {
+            if !stmt.params.is_empty() {
+                builder.push(' ');
+            }
+            _ = write!(&mut builder, "...{}", rest.lexeme());
         }
         builder.push(')');
         builder
@@ -223,15 +277,93 @@ impl StmtVisitor<String> for AstPrinter {
         )
     }
 
+    fn visit_match_stmt(&mut self, stmt: &Match) -> String {
+        let mut builder = format!("(match {}", stmt.value().accept(self));
+        for arm in stmt.arms() {
+            let pattern = match &arm.pattern {
+                Pattern::Literal { value, .. } => value.to_string(),
+                Pattern::Class { class, binding } => {
+                    format!("{} {}", class.name().lexeme(), binding.lexeme())
+                },
+                Pattern::Wildcard(tk) => tk.lexeme().to_owned(),
+            };
+            _ = write!(&mut builder, " (=> {pattern} {})", arm.body.accept(self));
+        }
+        builder.push(')');
+        builder
+    }
+
     fn visit_class_stmt(&mut self, stmt: &Class) -> String {
         let mut builder = format!("(class {}", stmt.name().lexeme());
         if let Some(superclass) = stmt.superclass() {
-            write!(&mut builder, " < {}", superclass.accept(self)).unwrap();
+            _ = write!(&mut builder, " < {}", superclass.accept(self));
+        }
+        if !stmt.traits().is_empty() {
+            builder.push_str(" with");
+            for ele in stmt.traits() {
+                _ = write!(&mut builder, " {}", ele.accept(self));
+            }
         }
         for ele in stmt.methods() {
-            write!(&mut builder, " {}", ele.accept(self)).unwrap();
+            _ = write!(&mut builder, " {}", ele.accept(self));
+        }
+        for ele in stmt.static_methods() {
+            _ = write!(&mut builder, " (static {})", ele.accept(self));
+        }
+        for ele in stmt.getters() {
+            _ = write!(&mut builder, " (getter {})", ele.accept(self));
+        }
+        builder.push(')');
+        builder
+    }
+
+    fn visit_trait_stmt(&mut self, stmt: &Trait) -> String {
+        let mut builder = format!("(trait {}", stmt.name().lexeme());
+        for ele in stmt.methods() {
+            _ = write!(&mut builder, " {}", ele.accept(self));
+        }
+        builder.push(')');
+        builder
+    }
+
+    fn visit_import_stmt(&mut self, stmt: &Import) -> String {
+        format!(
+            "(import {} as {})",
+            stmt.path().lexeme(),
+            stmt.name().lexeme()
+        )
+    }
+
+    fn visit_throw_stmt(&mut self, stmt: &Throw) -> String {
+        self.parenthesize("throw", [stmt.value()])
+    }
+
+    fn visit_try_stmt(&mut self, stmt: &Try) -> String {
+        let mut builder = String::from("(try (block ");
+        for ele in stmt.body() {
+            builder.push_str(&ele.accept(self));
+        }
+        builder.push(')');
+        if let Some(catch) = stmt.catch() {
+            _ = write!(&mut builder, " (catch {} ", catch.binding.lexeme());
+            for ele in &catch.body {
+                builder.push_str(&ele.accept(self));
+            }
+            builder.push(')');
+        }
+        if let Some(finally) = stmt.finally() {
+            builder.push_str(" (finally ");
+            for ele in finally {
+                builder.push_str(&ele.accept(self));
+            }
+            builder.push(')');
         }
         builder.push(')');
         builder
     }
+
+    fn visit_yield_stmt(&mut self, stmt: &Yield) -> String {
+        stmt.value()
+            .map_or_else(|| "(yield)".to_owned(), |v| self.parenthesize("yield", [v]))
+    }
 }
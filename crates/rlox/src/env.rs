@@ -22,7 +22,7 @@ pub type Result<T> = core::result::Result<T, EnvError>;
 #[derive(Default)]
 #[derive(PartialEq, Eq)]
 pub struct Environment {
-    enclosing: Option<Rc<RefCell<Environment>>>,
+    enclosing: Option<Rc<RefCell<Self>>>,
     /// NOTE: Use [`std::rc::Rc`] pointers to avoid expensive cloning of [`Environment::ancestor`]
     values: Rc<RefCell<HashMap<String, LiteralType>>>,
 }
@@ -120,6 +120,22 @@ impl Environment {
         Ok(())
     }
 
+    /// Read from the outermost scope, the globals of the running file
+    pub fn get_global(&self, name: &Token) -> Option<LiteralType> {
+        if let Some(enclosing) = &self.enclosing {
+            return enclosing.borrow().get_global(name);
+        }
+        self.values.borrow().get(name.lexeme()).cloned()
+    }
+
+    /// Assign in the outermost scope, the globals of the running file
+    pub fn assign_global(&self, name: &Token, value: LiteralType) -> Result<()> {
+        if let Some(enclosing) = &self.enclosing {
+            return enclosing.borrow().assign_global(name, value);
+        }
+        self.assign(name, value)
+    }
+
     pub const fn enclosing(&self) -> Option<&Rc<RefCell<Self>>> {
         self.enclosing.as_ref()
     }
@@ -2,7 +2,19 @@
 
 use std::{cell::RefCell, fmt::Display, hash::Hash, rc::Rc};
 
-use crate::{lox_callable::Callables, lox_instance::LoxInstance, token::Token};
+use indexmap::IndexMap;
+
+use crate::{
+    lox_callable::Callables,
+    lox_fun::SeqIter,
+    lox_generator::LoxGenerator,
+    lox_instance::LoxInstance,
+    lox_module::LoxModule,
+    lox_trait::LoxTrait,
+    span::Span,
+    stmt::{Function, Unpack},
+    token::Token,
+};
 
 pub trait Expr {
     fn accept<R>(&self, visitor: &mut dyn ExprVisitor<R>) -> R;
@@ -40,7 +52,38 @@ impl Expr for Exprs {
     }
 }
 
+impl Exprs {
+    pub const fn span(&self) -> Span {
+        match self {
+        $(
+            Self::$variant(inner) => inner.span,
+        )*
+        }
+    }
+
+    #[must_use]
+    pub fn with_span(self, span: Span) -> Self {
+        match self {
+        $(
+            Self::$variant(inner) => Self::$variant(inner.with_span(span)),
+        )*
+        }
+    }
+}
+
 $(
+    impl $variant {
+        pub const fn span(&self) -> Span {
+            self.span
+        }
+
+        #[must_use]
+        pub const fn with_span(mut self, span: Span) -> Self {
+            self.span = span;
+            self
+        }
+    }
+
     impl Expr for $variant {
         fn accept<R>(&self, visitor: &mut dyn ExprVisitor<R>) -> R
         {
@@ -54,7 +97,25 @@ $(
 }
 
 expr_gen!(
-    Assign, Binary, Call, Get, Grouping, Literal, Logical, Set, Super, This, Unary, Variable
+    Assign,
+    AssignUnpack,
+    Binary,
+    Call,
+    Conditional,
+    Get,
+    Grouping,
+    Index,
+    Lambda,
+    List,
+    Literal,
+    Map,
+    Logical,
+    Set,
+    SetIndex,
+    Super,
+    This,
+    Unary,
+    Variable
 );
 
 #[derive(Debug)]
@@ -63,6 +124,9 @@ expr_gen!(
 pub struct Assign {
     name: Token,
     value: Box<Exprs>,
+    /// `+=`, `-=`, ... `None` for a plain `=`
+    operator: Option<Token>,
+    span: Span,
 }
 
 impl Display for Assign {
@@ -76,9 +140,18 @@ impl Assign {
         Self {
             name,
             value: Box::new(value),
+            operator: None,
+            span: Span::new(0, 0),
         }
     }
 
+    /// Make it a compound assignment, `name op= value`
+    #[must_use]
+    pub fn with_operator(mut self, operator: Token) -> Self {
+        self.operator = Some(operator);
+        self
+    }
+
     pub const fn value(&self) -> &Exprs {
         &self.value
     }
@@ -86,6 +159,43 @@ impl Assign {
     pub const fn name(&self) -> &Token {
         &self.name
     }
+
+    pub const fn operator(&self) -> Option<&Token> {
+        self.operator.as_ref()
+    }
+}
+
+/// `[a, ...rest] = value` or `{x, y} = value`, to names declared before
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq, Eq, Hash)]
+pub struct AssignUnpack {
+    pattern: Unpack,
+    value: Box<Exprs>,
+    span: Span,
+}
+
+impl AssignUnpack {
+    pub fn new(pattern: Unpack, value: Exprs) -> Self {
+        Self {
+            pattern,
+            value: Box::new(value),
+            span: Span::new(0, 0),
+        }
+    }
+
+    pub const fn pattern(&self) -> &Unpack {
+        &self.pattern
+    }
+
+    pub const fn value(&self) -> &Exprs {
+        &self.value
+    }
+
+    /// What a bound name is resolved as, the same for the resolver and the interpreter
+    pub fn target(name: &Token) -> Exprs {
+        Exprs::Variable(Variable::new(name.clone()).with_span(Span::from(name)))
+    }
 }
 
 #[derive(Debug)]
@@ -95,6 +205,7 @@ pub struct Binary {
     left: Box<Exprs>,
     operator: Token,
     right: Box<Exprs>,
+    span: Span,
 }
 
 impl Binary {
@@ -103,6 +214,7 @@ impl Binary {
             left: Box::new(left),
             operator,
             right: Box::new(right),
+            span: Span::new(0, 0),
         }
     }
 
@@ -126,6 +238,7 @@ pub struct Call {
     callee: Box<Exprs>,
     name: Token,
     arguments: Vec<Exprs>,
+    span: Span,
 }
 
 impl Call {
@@ -134,6 +247,7 @@ impl Call {
             callee: Box::new(callee),
             name: paren,
             arguments,
+            span: Span::new(0, 0),
         }
     }
 
@@ -156,6 +270,7 @@ impl Call {
 pub struct Get {
     pub object: Box<Exprs>,
     pub name: Token,
+    span: Span,
 }
 
 impl Get {
@@ -163,6 +278,7 @@ impl Get {
         Self {
             object: Box::new(object),
             name,
+            span: Span::new(0, 0),
         }
     }
 
@@ -180,12 +296,14 @@ impl Get {
 #[derive(PartialEq, Eq, Hash)]
 pub struct Grouping {
     expression: Box<Exprs>,
+    span: Span,
 }
 
 impl Grouping {
     pub fn new(expression: Exprs) -> Self {
         Self {
             expression: Box::new(expression),
+            span: Span::new(0, 0),
         }
     }
 
@@ -200,11 +318,15 @@ impl Grouping {
 #[derive(PartialEq, Eq, Hash)]
 pub struct Literal {
     value: LiteralType,
+    span: Span,
 }
 
 impl Literal {
     pub const fn new(value: LiteralType) -> Self {
-        Self { value }
+        Self {
+            value,
+            span: Span::new(0, 0),
+        }
     }
 
     pub const fn value(&self) -> &LiteralType {
@@ -215,7 +337,6 @@ impl Literal {
 #[derive(Clone)]
 #[derive(Debug)]
 #[derive(Default)]
-#[derive(PartialEq)]
 pub enum LiteralType {
     String(String),
     Number(f64),
@@ -224,6 +345,14 @@ pub enum LiteralType {
     Nil,
     Callable(Callables),
     LoxInstance(Rc<RefCell<LoxInstance>>),
+    List(Rc<RefCell<Vec<Self>>>),
+    /// keys keep the insertion order
+    Map(Rc<RefCell<IndexMap<Self, Self>>>),
+    /// the hidden cursor of a `for (var x in xs)` loop
+    SeqIter(Rc<RefCell<SeqIter>>),
+    Trait(Rc<LoxTrait>),
+    Module(Rc<LoxModule>),
+    Generator(Rc<LoxGenerator>),
 }
 
 impl Hash for LiteralType {
@@ -235,12 +364,55 @@ impl Hash for LiteralType {
             Self::Nil => "nil".hash(state),
             Self::Callable(callables) => callables.hash(state),
             Self::LoxInstance(instance) => instance.borrow().hash(state),
+            // a list may hold itself
+            Self::List(list) => list.borrow().len().hash(state),
+            // equal maps may have a different order
+            Self::Map(map) => map.borrow().len().hash(state),
+            Self::SeqIter(seq) => seq.borrow().hash(state),
+            Self::Trait(lox_trait) => lox_trait.hash(state),
+            Self::Module(module) => module.hash(state),
+            Self::Generator(generator) => generator.hash(state),
+        }
+    }
+}
+
+impl PartialEq for LiteralType {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::String(a), Self::String(b)) => a == b,
+            (Self::Number(a), Self::Number(b)) => a == b,
+            (Self::Bool(a), Self::Bool(b)) => a == b,
+            (Self::Nil, Self::Nil) => true,
+            (Self::Callable(a), Self::Callable(b)) => a == b,
+            (Self::LoxInstance(a), Self::LoxInstance(b)) => a == b,
+            (Self::List(a), Self::List(b)) => {
+                guard_compare((Rc::as_ptr(a).cast(), Rc::as_ptr(b).cast()), || a == b)
+            },
+            (Self::Map(a), Self::Map(b)) => {
+                guard_compare((Rc::as_ptr(a).cast(), Rc::as_ptr(b).cast()), || a == b)
+            },
+            (Self::SeqIter(a), Self::SeqIter(b)) => a == b,
+            (Self::Trait(a), Self::Trait(b)) => a == b,
+            (Self::Module(a), Self::Module(b)) => a == b,
+            (Self::Generator(a), Self::Generator(b)) => a == b,
+            _ => false,
         }
     }
 }
 
 impl Eq for LiteralType {}
 
+impl LiteralType {
+    /// Only immutable values can be map keys, `-0` and `0` are the same key
+    pub fn into_key(self) -> Option<Self> {
+        match self {
+            Self::Number(0.0) => Some(Self::Number(0.0)),
+            Self::String(_) | Self::Number(_) | Self::Bool(_) | Self::Nil => Some(self),
+            _ => None,
+        }
+    }
+}
+
 impl Display for LiteralType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         #![expect(clippy::enum_glob_use, reason = "happy")]
@@ -252,10 +424,71 @@ impl Display for LiteralType {
             Nil => f.write_fmt(format_args!("nil")),
             Callable(v) => v.fmt(f),
             LoxInstance(instance) => instance.borrow().fmt(f),
+            List(list) => guard_cycle(f, Rc::as_ptr(list).cast(), "[...]", |f| {
+                f.write_str("[")?;
+                for (i, ele) in list.borrow().iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    ele.fmt(f)?;
+                }
+                f.write_str("]")
+            }),
+            Map(map) => guard_cycle(f, Rc::as_ptr(map).cast(), "{...}", |f| {
+                f.write_str("{")?;
+                for (i, (key, value)) in map.borrow().iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{key}: {value}")?;
+                }
+                f.write_str("}")
+            }),
+            SeqIter(_) => f.write_str("<iterator>"),
+            Trait(lox_trait) => lox_trait.fmt(f),
+            Module(module) => module.fmt(f),
+            Generator(generator) => generator.fmt(f),
         }
     }
 }
 
+thread_local! {
+    /// Lists and maps being printed, innermost last
+    static PRINTING: RefCell<Vec<*const ()>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Prints with `print`, or `cycle` when `ptr` is already being printed further out
+fn guard_cycle(
+    f: &mut std::fmt::Formatter<'_>,
+    ptr: *const (),
+    cycle: &str,
+    print: impl FnOnce(&mut std::fmt::Formatter<'_>) -> std::fmt::Result,
+) -> std::fmt::Result {
+    if PRINTING.with_borrow(|printing| printing.contains(&ptr)) {
+        return f.write_str(cycle);
+    }
+    PRINTING.with_borrow_mut(|printing| printing.push(ptr));
+    let res = print(f);
+    PRINTING.with_borrow_mut(Vec::pop);
+    res
+}
+
+thread_local! {
+    /// Pairs of lists or maps being compared, innermost last
+    static COMPARING: RefCell<Vec<(*const (), *const ())>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Compares with `eq`, a pair already being compared further out is taken as equal
+fn guard_compare(pair: (*const (), *const ()), eq: impl FnOnce() -> bool) -> bool {
+    if pair.0 == pair.1 || COMPARING.with_borrow(|comparing| comparing.contains(&pair)) {
+        return true;
+    }
+    COMPARING.with_borrow_mut(|comparing| comparing.push(pair));
+    let res = eq();
+    COMPARING.with_borrow_mut(Vec::pop);
+    res
+}
+
 #[derive(Debug)]
 #[derive(Clone)]
 #[derive(PartialEq, Eq, Hash)]
@@ -263,6 +496,7 @@ pub struct Logical {
     left: Box<Exprs>,
     operator: Token,
     right: Box<Exprs>,
+    span: Span,
 }
 
 impl Logical {
@@ -271,6 +505,7 @@ impl Logical {
             left: Box::new(left),
             operator,
             right: Box::new(right),
+            span: Span::new(0, 0),
         }
     }
 
@@ -287,6 +522,202 @@ impl Logical {
     }
 }
 
+/// `[a, b, c]`
+#[derive(PartialEq, Eq, Hash)]
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct List {
+    elements: Vec<Exprs>,
+    span: Span,
+}
+
+impl List {
+    pub const fn new(elements: Vec<Exprs>) -> Self {
+        Self {
+            elements,
+            span: Span::new(0, 0),
+        }
+    }
+
+    pub fn elements(&self) -> &[Exprs] {
+        &self.elements
+    }
+}
+
+/// `{key: value, ...}`
+#[derive(PartialEq, Eq, Hash)]
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Map {
+    /// the `{`, where bad keys are reported
+    brace: Token,
+    entries: Vec<(Exprs, Exprs)>,
+    span: Span,
+}
+
+impl Map {
+    pub const fn new(brace: Token, entries: Vec<(Exprs, Exprs)>) -> Self {
+        Self {
+            brace,
+            entries,
+            span: Span::new(0, 0),
+        }
+    }
+
+    pub const fn brace(&self) -> &Token {
+        &self.brace
+    }
+
+    pub fn entries(&self) -> &[(Exprs, Exprs)] {
+        &self.entries
+    }
+}
+
+/// `object[index]`
+#[derive(PartialEq, Eq, Hash)]
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Index {
+    object: Box<Exprs>,
+    /// the `[`, where errors are reported
+    bracket: Token,
+    index: Box<Exprs>,
+    span: Span,
+}
+
+impl Index {
+    pub fn new(object: Exprs, bracket: Token, index: Exprs) -> Self {
+        Self {
+            object: Box::new(object),
+            bracket,
+            index: Box::new(index),
+            span: Span::new(0, 0),
+        }
+    }
+
+    pub const fn object(&self) -> &Exprs {
+        &self.object
+    }
+
+    pub const fn bracket(&self) -> &Token {
+        &self.bracket
+    }
+
+    pub const fn index(&self) -> &Exprs {
+        &self.index
+    }
+}
+
+/// `object[index] = value`
+#[derive(PartialEq, Eq, Hash)]
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct SetIndex {
+    object: Box<Exprs>,
+    bracket: Token,
+    index: Box<Exprs>,
+    value: Box<Exprs>,
+    /// `+=`, `-=`, ... `None` for a plain `=`
+    operator: Option<Token>,
+    span: Span,
+}
+
+impl SetIndex {
+    pub fn new(index: Index, value: Exprs) -> Self {
+        Self {
+            object: index.object,
+            bracket: index.bracket,
+            index: index.index,
+            value: Box::new(value),
+            operator: None,
+            span: Span::new(0, 0),
+        }
+    }
+
+    /// Make it a compound assignment, `object[index] op= value`
+    #[must_use]
+    pub fn with_operator(mut self, operator: Token) -> Self {
+        self.operator = Some(operator);
+        self
+    }
+
+    pub const fn object(&self) -> &Exprs {
+        &self.object
+    }
+
+    pub const fn bracket(&self) -> &Token {
+        &self.bracket
+    }
+
+    pub const fn index(&self) -> &Exprs {
+        &self.index
+    }
+
+    pub const fn value(&self) -> &Exprs {
+        &self.value
+    }
+
+    pub const fn operator(&self) -> Option<&Token> {
+        self.operator.as_ref()
+    }
+}
+
+/// `fun (a, b) { ... }`, the name of the function is the `fun` keyword
+#[derive(PartialEq, Eq, Hash)]
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Lambda {
+    function: Function,
+    span: Span,
+}
+
+impl Lambda {
+    pub const fn new(function: Function) -> Self {
+        Self {
+            function,
+            span: Span::new(0, 0),
+        }
+    }
+
+    pub const fn function(&self) -> &Function {
+        &self.function
+    }
+}
+
+/// `condition ? then_branch : else_branch`
+#[derive(PartialEq, Eq, Hash)]
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Conditional {
+    condition: Box<Exprs>,
+    then_branch: Box<Exprs>,
+    else_branch: Box<Exprs>,
+    span: Span,
+}
+
+impl Conditional {
+    pub fn new(condition: Exprs, then_branch: Exprs, else_branch: Exprs) -> Self {
+        Self {
+            condition: Box::new(condition),
+            then_branch: Box::new(then_branch),
+            else_branch: Box::new(else_branch),
+            span: Span::new(0, 0),
+        }
+    }
+
+    pub const fn condition(&self) -> &Exprs {
+        &self.condition
+    }
+
+    pub const fn then_branch(&self) -> &Exprs {
+        &self.then_branch
+    }
+
+    pub const fn else_branch(&self) -> &Exprs {
+        &self.else_branch
+    }
+}
+
 #[derive(PartialEq, Eq, Hash)]
 #[derive(Debug)]
 #[derive(Clone)]
@@ -294,6 +725,9 @@ pub struct Set {
     object: Box<Exprs>,
     name: Token,
     value: Box<Exprs>,
+    /// `+=`, `-=`, ... `None` for a plain `=`
+    operator: Option<Token>,
+    span: Span,
 }
 
 impl Set {
@@ -302,9 +736,22 @@ impl Set {
             object: Box::new(object),
             name,
             value: Box::new(value),
+            operator: None,
+            span: Span::new(0, 0),
         }
     }
 
+    /// Make it a compound assignment, `object.name op= value`
+    #[must_use]
+    pub fn with_operator(mut self, operator: Token) -> Self {
+        self.operator = Some(operator);
+        self
+    }
+
+    pub const fn operator(&self) -> Option<&Token> {
+        self.operator.as_ref()
+    }
+
     pub const fn object(&self) -> &Exprs {
         &self.object
     }
@@ -324,11 +771,16 @@ impl Set {
 pub struct Super {
     keyword: Token,
     method: Token,
+    span: Span,
 }
 
 impl Super {
     pub const fn new(keyword: Token, method: Token) -> Self {
-        Self { keyword, method }
+        Self {
+            keyword,
+            method,
+            span: Span::new(0, 0),
+        }
     }
 
     pub const fn keyword(&self) -> &Token {
@@ -345,11 +797,15 @@ impl Super {
 #[derive(PartialEq, Eq, Hash, PartialOrd)]
 pub struct This {
     keyword: Token,
+    span: Span,
 }
 
 impl This {
     pub const fn new(keyword: Token) -> Self {
-        Self { keyword }
+        Self {
+            keyword,
+            span: Span::new(0, 0),
+        }
     }
 
     pub const fn keyword(&self) -> &Token {
@@ -363,6 +819,7 @@ impl This {
 pub struct Unary {
     operator: Token,
     right: Box<Exprs>,
+    span: Span,
 }
 
 impl Unary {
@@ -370,6 +827,7 @@ impl Unary {
         Self {
             operator,
             right: Box::new(right),
+            span: Span::new(0, 0),
         }
     }
 
@@ -387,11 +845,15 @@ impl Unary {
 #[derive(PartialEq, Eq, Hash, PartialOrd)]
 pub struct Variable {
     name: Token,
+    span: Span,
 }
 
 impl Variable {
     pub const fn new(name: Token) -> Self {
-        Self { name }
+        Self {
+            name,
+            span: Span::new(0, 0),
+        }
     }
 
     pub const fn name(&self) -> &Token {
@@ -1,15 +1,30 @@
 #[cfg(test)]
 mod test;
 
-use std::{cell::RefCell, collections::HashMap, rc::Rc, time::SystemTimeError};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::SystemTimeError,
+};
+
+use indexmap::IndexMap;
 
 use crate::{
     env::Environment,
     expr::*,
-    lox_callable::{Callables, LoxCallable},
+    lox_callable::{Arity, Callables, LoxCallable},
     lox_class::LoxClass,
-    lox_fun::{ClockFunction, LoxFunction},
+    lox_fun::{ClockFunction, LoxFunction, NativeFunction},
+    lox_generator::LoxGenerator,
+    lox_instance::LoxInstance,
+    lox_module::LoxModule,
+    lox_trait::LoxTrait,
+    parser::Parser,
+    resolver::Resolver,
     r#return::FnReturn,
+    scan::scanner::Scanner,
     stmt::*,
     token::{Token, TokenInner},
 };
@@ -31,21 +46,23 @@ pub enum InterError {
     // TODO: maybe use Result<Break, Error>
     #[error("Should not use `break` out of loop: {0}")]
     NeedBreak(Token),
+    #[error("Should not use `continue` out of loop: {0}")]
+    NeedContinue(Token),
     #[error("{0}")]
     Message(String),
     #[error("Can not call: {0}")]
     NotCallable(Token),
-    #[error("Args arity not match: {tk}, expected: {expect}, but got {actual}")]
+    #[error("Args arity not match: {tk}, expected {expect} arguments, but got {actual}")]
     ArgsArity {
         tk: Token,
-        expect: usize,
+        expect: Arity,
         actual: usize,
     },
     #[error("Get time failed: {0}")]
     Time(#[from] SystemTimeError),
     // TODO: maybe use Result<Resturn, Error>
     #[error("Fn return value: {0}")]
-    Return(crate::r#return::FnReturn),
+    Return(Box<crate::r#return::FnReturn>),
     #[error(transparent)]
     Env(#[from] crate::env::EnvError),
     #[error("Undefined property: {0}")]
@@ -54,10 +71,109 @@ pub enum InterError {
     NotInstance(Token),
     #[error("Superclass must be a class: {0}")]
     Superclass(Token),
+    #[error("Only traits can be used with `with`: {0}")]
+    NotTrait(Token),
+    #[error("Method `{method}` is defined by more than one trait: {tk}")]
+    TraitConflict { tk: Token, method: String },
+    #[error("Pattern must name a class: {0}")]
+    PatternClass(Token),
+    #[error("Only lists and maps can be indexed: {0}")]
+    NotIndexable(Token),
+    #[error("Index must be an integer: {0}")]
+    IndexNotInteger(Token),
+    #[error("Index {index} out of range for length {len}: {tk}")]
+    IndexOutOfRange { tk: Token, index: f64, len: usize },
+    #[error("Map key must be a string, number, bool or nil: {0}")]
+    MapKey(Token),
+    #[error("Undefined key `{key}`: {tk}")]
+    NoKey { tk: Token, key: String },
+    #[error("Can only iterate over lists, maps, strings and instances with `iter()`: {0}")]
+    NotIterable(Token),
+    #[error("Iterator must have a `{method}()` method: {tk}")]
+    Iterator { tk: Token, method: &'static str },
+    #[error("{msg}: {tk}")]
+    Native { tk: Token, msg: String },
+    #[error("Can't import module, {msg}: {tk}")]
+    Import { tk: Token, msg: String },
+    #[error("Circular import of `{path}`: {tk}")]
+    CircularImport { tk: Token, path: String },
+    #[error("Uncaught exception {value}: {tk}")]
+    Throw { tk: Token, value: Box<LiteralType> },
+    #[error("Can't yield outside a generator body: {0}")]
+    Yield(Token),
+    #[error("Can't destructure, {msg}: {tk}")]
+    Destructure { tk: Token, msg: String },
 }
 
 pub type Result<T> = core::result::Result<T, InterError>;
 
+impl InterError {
+    /// `return`, `break` and `continue` unwind through `try` but can't be caught
+    pub const fn is_unwinding(&self) -> bool {
+        matches!(
+            self,
+            Self::Return(_) | Self::NeedBreak(_) | Self::NeedContinue(_)
+        )
+    }
+
+    /// Source line where the error happened
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            Self::Number(inner) | Self::UnaryNumber(inner) | Self::Plus(inner) => {
+                Some(inner.line())
+            },
+            Self::NoVar(tk)
+            | Self::NeedBreak(tk)
+            | Self::NeedContinue(tk)
+            | Self::NotCallable(tk)
+            | Self::ArgsArity { tk, .. }
+            | Self::Env(crate::env::EnvError::UndefinedVar(tk))
+            | Self::NoProperty(tk)
+            | Self::NotInstance(tk)
+            | Self::Superclass(tk)
+            | Self::NotTrait(tk)
+            | Self::Yield(tk)
+            | Self::Destructure { tk, .. }
+            | Self::TraitConflict { tk, .. }
+            | Self::PatternClass(tk)
+            | Self::NotIndexable(tk)
+            | Self::IndexNotInteger(tk)
+            | Self::IndexOutOfRange { tk, .. }
+            | Self::MapKey(tk)
+            | Self::NoKey { tk, .. }
+            | Self::NotIterable(tk)
+            | Self::Iterator { tk, .. }
+            | Self::Native { tk, .. }
+            | Self::Import { tk, .. }
+            | Self::CircularImport { tk, .. }
+            | Self::Throw { tk, .. } => Some(tk.inner().line()),
+            Self::NotMatch(_)
+            | Self::Message(_)
+            | Self::Time(_)
+            | Self::Return(_)
+            | Self::Env(_) => None,
+        }
+    }
+
+    /// The value a `catch` binds: a thrown value as is, otherwise an `Error` instance
+    /// with `message` and `line`
+    pub fn into_value(self) -> LiteralType {
+        if let Self::Throw { value, .. } = self {
+            return *value;
+        }
+        let mut error = LoxInstance::new(LoxClass::error());
+        error.set_field(
+            "line".to_owned(),
+            self.line().map_or(LiteralType::Nil, |line| {
+                #[expect(clippy::cast_precision_loss, reason = "line numbers are small")]
+                LiteralType::Number(line as f64)
+            }),
+        );
+        error.set_field("message".to_owned(), LiteralType::String(self.to_string()));
+        LiteralType::LoxInstance(Rc::new(RefCell::new(error)))
+    }
+}
+
 #[derive(Clone)]
 #[derive(Debug)]
 #[derive(PartialEq, Eq)]
@@ -65,6 +181,12 @@ pub struct Interpreter {
     pub globals: Rc<RefCell<Environment>>,
     pub environment: Rc<RefCell<Environment>>,
     locals: HashMap<Exprs, usize>,
+    /// global `const` names, kept across resolver runs of a REPL session
+    const_globals: HashSet<String>,
+    /// imported files by canonical path, each runs once
+    modules: HashMap<PathBuf, Rc<LoxModule>>,
+    /// the running file and the files importing it, innermost last
+    files: Vec<PathBuf>,
 }
 
 impl Default for Interpreter {
@@ -75,17 +197,88 @@ impl Default for Interpreter {
 
 impl Interpreter {
     pub fn new() -> Self {
+        let globals = Rc::new(RefCell::new(Self::prelude()));
+        Self {
+            globals: Rc::clone(&globals),
+            environment: Rc::clone(&globals),
+            locals: HashMap::new(),
+            const_globals: HashSet::new(),
+            modules: HashMap::new(),
+            files: Vec::new(),
+        }
+    }
+
+    /// Globals of a fresh file, the native functions
+    fn prelude() -> Environment {
         let globals = Environment::new();
         globals.define(
             "clock".to_owned(),
             LiteralType::Callable(Callables::Clock(ClockFunction)),
         );
-        let globals = Rc::new(RefCell::new(globals));
-        Self {
-            globals: Rc::clone(&globals),
-            environment: Rc::clone(&globals),
-            locals: HashMap::new(),
+        for native in NativeFunction::ALL {
+            globals.define(
+                native.name().to_owned(),
+                LiteralType::Callable(Callables::Native(native)),
+            );
         }
+        globals.define(
+            "Error".to_owned(),
+            LiteralType::Callable(Callables::Class(LoxClass::error())),
+        );
+        globals
+    }
+
+    /// Imports in the script resolve relative to `path`
+    pub fn set_main_file(&mut self, path: &Path) {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        self.files = vec![path];
+    }
+
+    /// Scan, parse, resolve and run the file at `path` in its own globals
+    fn load_module(&mut self, path: &Path, tk: &Token) -> Result<LoxModule> {
+        let import_err = |msg: String| InterError::Import {
+            tk: tk.clone(),
+            msg,
+        };
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| import_err(format!("failed to read `{}`: {e}", path.display())))?;
+
+        let mut scanner = Scanner::new(&source);
+        let tokens: Vec<_> = scanner.scan_tokens().collect();
+        if !scanner.errors().is_empty() {
+            for e in scanner.errors() {
+                tracing::error!("{e}");
+            }
+            return Err(import_err(format!("scan err in `{}`", path.display())));
+        }
+
+        let (statements, errors) = Parser::new(tokens).parse();
+        if !errors.is_empty() {
+            for e in &errors {
+                tracing::error!("{e}");
+            }
+            return Err(import_err(format!(
+                "parse err in `{}`, found {} problems",
+                path.display(),
+                errors.len()
+            )));
+        }
+
+        // the module's global consts are its own
+        let const_globals = std::mem::take(&mut self.const_globals);
+        let had_err = Resolver::new(self).resolve(&statements);
+        self.const_globals = const_globals;
+        if had_err {
+            return Err(import_err(format!("resolver err in `{}`", path.display())));
+        }
+
+        let env = Self::prelude();
+        self.files.push(path.to_path_buf());
+        let res = self.execute_block(&statements, env.clone());
+        self.files.pop();
+        res?;
+
+        Ok(LoxModule::new(path.display().to_string(), env))
     }
 
     pub fn interpret(&mut self, exprs: &mut [Stmts]) -> Result<()> {
@@ -107,7 +300,142 @@ impl Interpreter {
         self.locals.insert(expr.clone(), depth);
     }
 
-    const fn is_truthy(literal: &LiteralType) -> bool {
+    pub(crate) fn define_const_global(&mut self, name: &str) {
+        self.const_globals.insert(name.to_owned());
+    }
+
+    pub(crate) fn is_const_global(&self, name: &str) -> bool {
+        self.const_globals.contains(name)
+    }
+
+    /// `left operator right`, a compound assignment operator works like its binary operator
+    fn binary_op(left: LiteralType, operator: &Token, right: LiteralType) -> Result<LiteralType> {
+        match operator {
+            Token::Plus { inner } | Token::PlusEqual { inner } => match (left, right) {
+                (LiteralType::Number(left), LiteralType::Number(right)) => {
+                    let var_name = left + right;
+                    Ok(LiteralType::Number(var_name))
+                },
+                (LiteralType::String(left), LiteralType::String(right)) => {
+                    let var_name = left + &right;
+                    Ok(LiteralType::String(var_name))
+                },
+                _ => Err(InterError::Plus(inner.clone())),
+            },
+            Token::Minus { inner } | Token::MinusEqual { inner } => {
+                if let (LiteralType::Number(left), LiteralType::Number(right)) = (left, right) {
+                    let var_name = left - right;
+                    return Ok(LiteralType::Number(var_name));
+                }
+                Err(InterError::Number(inner.clone()))
+            },
+            Token::Slash { inner } | Token::SlashEqual { inner } => {
+                if let (LiteralType::Number(left), LiteralType::Number(right)) = (left, right) {
+                    let var_name = left / right;
+                    return Ok(LiteralType::Number(var_name));
+                }
+                Err(InterError::Number(inner.clone()))
+            },
+            Token::Star { inner } | Token::StarEqual { inner } => {
+                if let (LiteralType::Number(left), LiteralType::Number(right)) = (left, right) {
+                    let var_name = left * right;
+                    return Ok(LiteralType::Number(var_name));
+                }
+                Err(InterError::Number(inner.clone()))
+            },
+            Token::Percent { inner } | Token::PercentEqual { inner } => {
+                if let (LiteralType::Number(left), LiteralType::Number(right)) = (left, right) {
+                    #[expect(clippy::modulo_arithmetic, reason = "sign of the dividend, like C")]
+                    let var_name = left % right;
+                    return Ok(LiteralType::Number(var_name));
+                }
+                Err(InterError::Number(inner.clone()))
+            },
+            Token::StarStar { inner } => {
+                if let (LiteralType::Number(left), LiteralType::Number(right)) = (left, right) {
+                    let var_name = left.powf(right);
+                    return Ok(LiteralType::Number(var_name));
+                }
+                Err(InterError::Number(inner.clone()))
+            },
+            Token::Greater { inner } => {
+                if let (LiteralType::Number(left), LiteralType::Number(right)) = (left, right) {
+                    let var_name = left > right;
+                    return Ok(LiteralType::Bool(var_name));
+                }
+                Err(InterError::Number(inner.clone()))
+            },
+            Token::GreaterEqual { inner } => {
+                if let (LiteralType::Number(left), LiteralType::Number(right)) = (left, right) {
+                    let var_name = left >= right;
+                    return Ok(LiteralType::Bool(var_name));
+                }
+                Err(InterError::Number(inner.clone()))
+            },
+            Token::Less { inner } => {
+                if let (LiteralType::Number(left), LiteralType::Number(right)) = (left, right) {
+                    let var_name = left < right;
+                    return Ok(LiteralType::Bool(var_name));
+                }
+                Err(InterError::Number(inner.clone()))
+            },
+            Token::LessEqual { inner } => {
+                if let (LiteralType::Number(left), LiteralType::Number(right)) = (left, right) {
+                    let var_name = left <= right;
+                    return Ok(LiteralType::Bool(var_name));
+                }
+                Err(InterError::Number(inner.clone()))
+            },
+            Token::BangEqual { .. } => {
+                let b = !Self::is_equal(&left, &right);
+                Ok(LiteralType::Bool(b))
+            },
+            Token::EqualEqual { .. } => {
+                let b = Self::is_equal(&left, &right);
+                Ok(LiteralType::Bool(b))
+            },
+            _ => Err(InterError::NotMatch("unreachable binary expr".to_owned())),
+        }
+    }
+
+    /// The list and the checked position of `object[index]`
+    fn list_index(
+        object: LiteralType,
+        index: &LiteralType,
+        bracket: &Token,
+    ) -> Result<(Rc<RefCell<Vec<LiteralType>>>, usize)> {
+        let LiteralType::List(list) = object
+        else {
+            return Err(InterError::NotIndexable(bracket.clone()));
+        };
+        let &LiteralType::Number(index) = index
+        else {
+            return Err(InterError::IndexNotInteger(bracket.clone()));
+        };
+        if index.fract() != 0.0 {
+            return Err(InterError::IndexNotInteger(bracket.clone()));
+        }
+        let len = list.borrow().len();
+        if index < 0.0 || index >= len as f64 {
+            return Err(InterError::IndexOutOfRange {
+                tk: bracket.clone(),
+                index,
+                len,
+            });
+        }
+        #[expect(
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss,
+            reason = "checked above"
+        )]
+        Ok((list, index as usize))
+    }
+
+    fn map_key(key: LiteralType, tk: &Token) -> Result<LiteralType> {
+        key.into_key().ok_or_else(|| InterError::MapKey(tk.clone()))
+    }
+
+    pub(crate) const fn is_truthy(literal: &LiteralType) -> bool {
         match literal {
             LiteralType::Nil => false,
             LiteralType::Bool(v) => *v,
@@ -119,6 +447,54 @@ impl Interpreter {
         a == b
     }
 
+    /// Evaluate `expr` in `env`, e.g. a default parameter value
+    pub fn evaluate_in(&mut self, expr: &Exprs, env: Environment) -> Result<LiteralType> {
+        self.with_env(env, |inter| inter.evaluate(expr))
+    }
+
+    /// Runs `f` with `env` as the current environment
+    pub fn with_env<T, F>(&mut self, env: Environment, f: F) -> T
+    where
+        F: FnOnce(&mut Self) -> T,
+    {
+        let previous = Rc::clone(&self.environment);
+        self.environment = Rc::new(RefCell::new(env));
+        let res = f(self);
+        self.environment = previous;
+        res
+    }
+
+    /// The body of the first arm `value` matches, with the name a class pattern binds it to
+    pub fn match_arm<'m>(
+        &mut self,
+        stmt: &'m Match,
+        value: &LiteralType,
+    ) -> Result<Option<(&'m Stmts, Option<&'m Token>)>> {
+        for arm in stmt.arms() {
+            match &arm.pattern {
+                Pattern::Literal { value: literal, .. } => {
+                    if Self::is_equal(value, literal) {
+                        return Ok(Some((&arm.body, None)));
+                    }
+                },
+                Pattern::Wildcard(_) => return Ok(Some((&arm.body, None))),
+                Pattern::Class { class, binding } => {
+                    let LiteralType::Callable(Callables::Class(class)) =
+                        self.evaluate(&Exprs::Variable(class.clone()))?
+                    else {
+                        return Err(InterError::PatternClass(class.name().clone()));
+                    };
+                    if let LiteralType::LoxInstance(instance) = value
+                        && instance.borrow().class().is_subclass_of(&class)
+                    {
+                        return Ok(Some((&arm.body, Some(binding))));
+                    }
+                },
+            }
+        }
+        Ok(None)
+    }
+
     pub fn execute_block(&mut self, statements: &[Stmts], env: Environment) -> Result<()> {
         let previous = Rc::clone(&self.environment);
         self.environment = Rc::new(RefCell::new(env));
@@ -141,12 +517,87 @@ impl Interpreter {
             Ok(var)
         }
         else {
-            self.globals
+            // the globals of the file the code is written in
+            self.environment
                 .borrow()
-                .get(name)
+                .get_global(name)
                 .ok_or_else(|| InterError::NoVar(name.clone()))
         }
     }
+
+    /// Pair each name of `pattern` with its part of `value`
+    fn unpack<'p>(
+        &mut self,
+        pattern: &'p Unpack,
+        value: LiteralType,
+    ) -> Result<Vec<(&'p Token, LiteralType)>> {
+        match pattern {
+            Unpack::List {
+                bracket,
+                names,
+                rest,
+            } => {
+                let LiteralType::List(list) = value
+                else {
+                    return Err(InterError::Destructure {
+                        tk: bracket.clone(),
+                        msg: format!("expect a list, got `{value}`"),
+                    });
+                };
+                let items = list.borrow().clone();
+                let (fits, expect) = match rest {
+                    Some(_) => (items.len() >= names.len(), "at least "),
+                    None => (items.len() == names.len(), ""),
+                };
+                if !fits {
+                    return Err(InterError::Destructure {
+                        tk: bracket.clone(),
+                        msg: format!(
+                            "expect {expect}{} elements, got {}",
+                            names.len(),
+                            items.len()
+                        ),
+                    });
+                }
+                let mut items = items.into_iter();
+                let mut bound: Vec<_> = names.iter().zip(items.by_ref()).collect();
+                if let Some(rest) = rest {
+                    let rest_items = LiteralType::List(Rc::new(RefCell::new(items.collect())));
+                    bound.push((rest, rest_items));
+                }
+                Ok(bound)
+            },
+            Unpack::Fields { brace, names } => {
+                let LiteralType::LoxInstance(instance) = value
+                else {
+                    return Err(InterError::Destructure {
+                        tk: brace.clone(),
+                        msg: format!("expect an instance, got `{value}`"),
+                    });
+                };
+                let mut bound = Vec::with_capacity(names.len());
+                for name in names {
+                    let field = LoxInstance::get(&instance, name, self)?
+                        .ok_or_else(|| InterError::NoProperty(name.clone()))?;
+                    bound.push((name, field));
+                }
+                Ok(bound)
+            },
+        }
+    }
+
+    /// Store `value` to the variable `key` resolved to, or to the global `name`
+    fn assign_variable(&self, key: &Exprs, name: &Token, value: LiteralType) -> Result<()> {
+        if let Some(distance) = self.locals.get(key) {
+            self.environment
+                .borrow()
+                .assign_at(*distance, name, value)?;
+        }
+        else {
+            self.environment.borrow().assign_global(name, value)?;
+        }
+        Ok(())
+    }
 }
 
 impl StmtVisitor<Result<()>> for Interpreter {
@@ -174,6 +625,16 @@ impl StmtVisitor<Result<()>> for Interpreter {
         Ok(())
     }
 
+    fn visit_destructure_stmt(&mut self, stmt: &Destructure) -> Result<()> {
+        let value = self.evaluate(stmt.value())?;
+        for (name, value) in self.unpack(stmt.pattern(), value)? {
+            self.environment
+                .borrow_mut()
+                .define(name.lexeme().to_owned(), value);
+        }
+        Ok(())
+    }
+
     fn visit_block_stmt(&mut self, stmt: &Block) -> Result<()> {
         self.execute_block(
             stmt.statements(),
@@ -195,10 +656,29 @@ impl StmtVisitor<Result<()>> for Interpreter {
         Ok(())
     }
 
+    fn visit_match_stmt(&mut self, stmt: &Match) -> Result<()> {
+        let value = self.evaluate(stmt.value())?;
+        match self.match_arm(stmt, &value)? {
+            Some((body, None)) => self.execute(body),
+            Some((body, Some(binding))) => {
+                let env = Environment::with_enclosing(Rc::clone(&self.environment));
+                env.define(binding.lexeme().to_owned(), value);
+                self.execute_block(std::slice::from_ref(body), env)
+            },
+            None => Ok(()),
+        }
+    }
+
     fn visit_while_stmt(&mut self, stmt: &While) -> Result<()> {
         let res: Result<()> = try {
             while Self::is_truthy(&self.evaluate(stmt.condition())?) {
-                self.execute(stmt.body())?;
+                match self.execute(stmt.body()) {
+                    Ok(()) | Err(InterError::NeedContinue(_)) => {},
+                    e @ Err(_) => e?,
+                }
+                if let Some(increment) = stmt.increment() {
+                    self.evaluate(increment)?;
+                }
             }
         };
         match res {
@@ -212,6 +692,10 @@ impl StmtVisitor<Result<()>> for Interpreter {
         Err(InterError::NeedBreak(stmt.token().clone()))
     }
 
+    fn visit_continue_stmt(&mut self, stmt: &Continue) -> Result<()> {
+        Err(InterError::NeedContinue(stmt.token().clone()))
+    }
+
     fn visit_function_stmt(&mut self, stmt: &Function) -> Result<()> {
         let fun = LoxFunction::new(stmt.clone(), Rc::clone(&self.environment), false);
         self.environment.borrow_mut().define(
@@ -225,9 +709,101 @@ impl StmtVisitor<Result<()>> for Interpreter {
     fn visit_return_stmt(&mut self, stmt: &Return) -> Result<()> {
         if let Some(v) = stmt.value() {
             let value = self.evaluate(v)?;
-            return Err(InterError::Return(FnReturn::new(value)));
+            return Err(InterError::Return(Box::new(FnReturn::new(value))));
+        }
+
+        Ok(())
+    }
+
+    fn visit_throw_stmt(&mut self, stmt: &Throw) -> Result<()> {
+        let value = self.evaluate(stmt.value())?;
+        Err(InterError::Throw {
+            tk: stmt.keyword().clone(),
+            value: Box::new(value),
+        })
+    }
+
+    fn visit_try_stmt(&mut self, stmt: &Try) -> Result<()> {
+        let res = self.execute_block(
+            stmt.body(),
+            Environment::with_enclosing(Rc::clone(&self.environment)),
+        );
+
+        let res = match (res, stmt.catch()) {
+            (Err(e), Some(catch)) if !e.is_unwinding() => {
+                let env = Environment::with_enclosing(Rc::clone(&self.environment));
+                env.define(catch.binding.lexeme().to_owned(), e.into_value());
+                self.execute_block(&catch.body, env)
+            },
+            (res, _) => res,
+        };
+
+        if let Some(finally) = stmt.finally() {
+            // an error or a jump out of `finally` replaces the pending one
+            self.execute_block(
+                finally,
+                Environment::with_enclosing(Rc::clone(&self.environment)),
+            )?;
         }
+        res
+    }
+
+    // a generator runs its `yield` itself, see `LoxGenerator`
+    fn visit_yield_stmt(&mut self, stmt: &Yield) -> Result<()> {
+        Err(InterError::Yield(stmt.keyword().clone()))
+    }
 
+    fn visit_import_stmt(&mut self, stmt: &Import) -> Result<()> {
+        let base = self
+            .files
+            .last()
+            .and_then(|file| file.parent())
+            .map_or_else(PathBuf::new, Path::to_path_buf);
+        let path = base.join(stmt.path().lexeme());
+        let path = path.canonicalize().map_err(|e| InterError::Import {
+            tk: stmt.path().clone(),
+            msg: format!("`{}`: {e}", path.display()),
+        })?;
+
+        if self.files.contains(&path) {
+            return Err(InterError::CircularImport {
+                tk: stmt.path().clone(),
+                path: path.display().to_string(),
+            });
+        }
+        let module = match self.modules.get(&path) {
+            Some(module) => Rc::clone(module),
+            None => {
+                let module = Rc::new(self.load_module(&path, stmt.path())?);
+                self.modules.insert(path, Rc::clone(&module));
+                module
+            },
+        };
+
+        self.environment
+            .borrow()
+            .define(stmt.name().lexeme().to_owned(), LiteralType::Module(module));
+        Ok(())
+    }
+
+    fn visit_trait_stmt(&mut self, stmt: &Trait) -> Result<()> {
+        let methods = stmt
+            .methods()
+            .iter()
+            .map(|method| {
+                let function = LoxFunction::new(
+                    method.clone(),
+                    Rc::clone(&self.environment),
+                    method.name.lexeme().eq("init"),
+                );
+                (method.name.lexeme().to_owned(), function)
+            })
+            .collect();
+        let lox_trait = LoxTrait::new(stmt.name().lexeme().to_owned(), methods);
+        self.environment.borrow().define(
+            stmt.name().lexeme().to_owned(),
+            LiteralType::Trait(Rc::new(lox_trait)),
+        );
         Ok(())
     }
 
@@ -243,6 +819,26 @@ impl StmtVisitor<Result<()>> for Interpreter {
             }
         }
 
+        // the class's own methods win, two traits providing the same one is a conflict
+        let mut trait_methods: HashMap<String, LoxFunction> = HashMap::new();
+        for trait_ in stmt.traits() {
+            let LiteralType::Trait(lox_trait) = self.evaluate(&Exprs::Variable(trait_.clone()))?
+            else {
+                return Err(InterError::NotTrait(trait_.name().clone()));
+            };
+            for (name, method) in lox_trait.methods() {
+                if stmt.methods().iter().any(|m| m.name.lexeme() == name) {
+                    continue;
+                }
+                if trait_methods.insert(name.clone(), method.clone()).is_some() {
+                    return Err(InterError::TraitConflict {
+                        tk: trait_.name().clone(),
+                        method: name.clone(),
+                    });
+                }
+            }
+        }
+
         let super_is_some = superclass.is_some();
 
         self.environment
@@ -268,12 +864,28 @@ impl StmtVisitor<Result<()>> for Interpreter {
             );
             methods.insert(method.name.lexeme().to_owned(), function);
         }
+        methods.extend(trait_methods);
+
+        let plain_functions = |functions: &[Function]| -> HashMap<String, LoxFunction> {
+            functions
+                .iter()
+                .map(|fun| {
+                    let function =
+                        LoxFunction::new(fun.clone(), Rc::clone(&self.environment), false);
+                    (fun.name.lexeme().to_owned(), function)
+                })
+                .collect()
+        };
+        let static_methods = plain_functions(stmt.static_methods());
+        let getters = plain_functions(stmt.getters());
 
         let klass = LoxClass::new(
             stmt.name().lexeme().to_owned(),
             superclass.map(Box::new),
             methods,
-        );
+        )
+        .with_static_methods(static_methods)
+        .with_getters(getters);
 
         if super_is_some {
             // use superclass env
@@ -292,18 +904,23 @@ impl StmtVisitor<Result<()>> for Interpreter {
 
 impl ExprVisitor<Result<LiteralType>> for Interpreter {
     fn visit_assign_expr(&mut self, expr: &Assign) -> Result<LiteralType> {
-        let value = self.evaluate(expr.value())?;
+        let value = match expr.operator() {
+            Some(operator) => {
+                let current = self.look_up_variable(expr.name(), &Exprs::Assign(expr.clone()))?;
+                let right = self.evaluate(expr.value())?;
+                Self::binary_op(current, operator, right)?
+            },
+            None => self.evaluate(expr.value())?,
+        };
 
-        let distance = self.locals.get(&Exprs::Assign(expr.clone()));
-        if let Some(distance) = distance {
-            self.environment
-                .borrow()
-                .assign_at(*distance, expr.name(), value.clone())?;
-        }
-        else {
-            self.globals
-                .borrow_mut()
-                .assign(expr.name(), value.clone())?;
+        self.assign_variable(&Exprs::Assign(expr.clone()), expr.name(), value.clone())?;
+        Ok(value)
+    }
+
+    fn visit_assignunpack_expr(&mut self, expr: &AssignUnpack) -> Result<LiteralType> {
+        let value = self.evaluate(expr.value())?;
+        for (name, part) in self.unpack(expr.pattern(), value.clone())? {
+            self.assign_variable(&AssignUnpack::target(name), name, part)?;
         }
         Ok(value)
     }
@@ -311,78 +928,7 @@ impl ExprVisitor<Result<LiteralType>> for Interpreter {
     fn visit_binary_expr(&mut self, expr: &Binary) -> Result<LiteralType> {
         let left = self.evaluate(expr.left())?;
         let right = self.evaluate(expr.right())?;
-
-        match expr.operator() {
-            Token::Plus { inner } => match (left, right) {
-                (LiteralType::Number(left), LiteralType::Number(right)) => {
-                    let var_name = left + right;
-                    Ok(LiteralType::Number(var_name))
-                },
-                (LiteralType::String(left), LiteralType::String(right)) => {
-                    let var_name = left + &right;
-                    Ok(LiteralType::String(var_name))
-                },
-                _ => Err(InterError::Plus(inner.clone())),
-            },
-            Token::Minus { inner } => {
-                if let (LiteralType::Number(left), LiteralType::Number(right)) = (left, right) {
-                    let var_name = left - right;
-                    return Ok(LiteralType::Number(var_name));
-                }
-                Err(InterError::Number(inner.clone()))
-            },
-            Token::Slash { inner } => {
-                if let (LiteralType::Number(left), LiteralType::Number(right)) = (left, right) {
-                    let var_name = left / right;
-                    return Ok(LiteralType::Number(var_name));
-                }
-                Err(InterError::Number(inner.clone()))
-            },
-            Token::Star { inner } => {
-                if let (LiteralType::Number(left), LiteralType::Number(right)) = (left, right) {
-                    let var_name = left * right;
-                    return Ok(LiteralType::Number(var_name));
-                }
-                Err(InterError::Number(inner.clone()))
-            },
-            Token::Greater { inner } => {
-                if let (LiteralType::Number(left), LiteralType::Number(right)) = (left, right) {
-                    let var_name = left > right;
-                    return Ok(LiteralType::Bool(var_name));
-                }
-                Err(InterError::Number(inner.clone()))
-            },
-            Token::GreaterEqual { inner } => {
-                if let (LiteralType::Number(left), LiteralType::Number(right)) = (left, right) {
-                    let var_name = left >= right;
-                    return Ok(LiteralType::Bool(var_name));
-                }
-                Err(InterError::Number(inner.clone()))
-            },
-            Token::Less { inner } => {
-                if let (LiteralType::Number(left), LiteralType::Number(right)) = (left, right) {
-                    let var_name = left < right;
-                    return Ok(LiteralType::Bool(var_name));
-                }
-                Err(InterError::Number(inner.clone()))
-            },
-            Token::LessEqual { inner } => {
-                if let (LiteralType::Number(left), LiteralType::Number(right)) = (left, right) {
-                    let var_name = left <= right;
-                    return Ok(LiteralType::Bool(var_name));
-                }
-                Err(InterError::Number(inner.clone()))
-            },
-            Token::BangEqual { .. } => {
-                let b = !Self::is_equal(&left, &right);
-                Ok(LiteralType::Bool(b))
-            },
-            Token::EqualEqual { .. } => {
-                let b = Self::is_equal(&left, &right);
-                Ok(LiteralType::Bool(b))
-            },
-            _ => Err(InterError::NotMatch("unreachable binary expr".to_owned())),
-        }
+        Self::binary_op(left, expr.operator(), right)
     }
 
     fn visit_call_expr(&mut self, expr: &Call) -> Result<LiteralType> {
@@ -395,19 +941,37 @@ impl ExprVisitor<Result<LiteralType>> for Interpreter {
         for arg in expr.arguments() {
             args.push(self.evaluate(arg)?);
         }
+        let arity = match &callee {
+            Callables::Fun(fun) => fun.arity(),
+            Callables::Clock(clock_function) => clock_function.arity(),
+            Callables::Str(str_function) => str_function.arity(),
+            // the initializer
+            Callables::Class(lox_class) => lox_class.arity(),
+            Callables::Native(native) => native.arity(),
+            Callables::Iter(_) => Arity::exact(1),
+            Callables::Generator(method) => method.arity(),
+        };
+        if !arity.contains(args.len()) {
+            return Err(InterError::ArgsArity {
+                tk: expr.name().clone(),
+                expect: arity,
+                actual: args.len(),
+            });
+        }
         let res = match callee {
-            Callables::Fun(fun) => {
-                if args.len() != fun.arity() {
-                    return Err(InterError::ArgsArity {
-                        tk: expr.name().clone(),
-                        expect: fun.arity(),
-                        actual: args.len(),
-                    });
-                }
-                fun.call(self, args)?
-            },
-            Callables::Clock(clock_function) => clock_function.call(self, vec![])?,
+            Callables::Fun(fun) => fun.call(self, args)?,
+            Callables::Clock(clock_function) => clock_function.call(self, args)?,
+            Callables::Str(str_function) => str_function.call(self, args)?,
             Callables::Class(lox_class) => lox_class.call(self, args)?,
+            Callables::Native(native) => native.apply(args).map_err(|msg| InterError::Native {
+                tk: expr.name().clone(),
+                msg,
+            })?,
+            Callables::Iter(iter) => {
+                let iterable = args.into_iter().next().unwrap_or(LiteralType::Nil);
+                iter.apply(self, iterable, expr.name())?
+            },
+            Callables::Generator(method) => method.call(self, args)?,
         };
         Ok(res)
     }
@@ -415,17 +979,74 @@ impl ExprVisitor<Result<LiteralType>> for Interpreter {
     fn visit_get_expr(&mut self, expr: &Get) -> Result<LiteralType> {
         let object = self.evaluate(expr.object())?;
         match object {
-            LiteralType::LoxInstance(instance) => instance
-                .borrow()
+            LiteralType::LoxInstance(instance) => LoxInstance::get(&instance, expr.name(), self)?
+                .ok_or_else(|| InterError::NoProperty(expr.name().clone())),
+            LiteralType::Module(module) => module
                 .get(expr.name())
-                .map_or_else(|| Err(InterError::NoProperty(expr.name().clone())), Ok),
+                .ok_or_else(|| InterError::NoProperty(expr.name().clone())),
+            LiteralType::Generator(generator) => LoxGenerator::get(&generator, expr.name())
+                .ok_or_else(|| InterError::NoProperty(expr.name().clone())),
+            LiteralType::Callable(Callables::Class(class)) => class
+                .find_static_method(expr.name().lexeme())
+                .map(|fun| LiteralType::Callable(Callables::Fun(fun)))
+                .ok_or_else(|| InterError::NoProperty(expr.name().clone())),
             _ => Err(InterError::NotInstance(expr.name().clone())),
         }
     }
+    fn visit_conditional_expr(&mut self, expr: &Conditional) -> Result<LiteralType> {
+        if Self::is_truthy(&self.evaluate(expr.condition())?) {
+            self.evaluate(expr.then_branch())
+        }
+        else {
+            self.evaluate(expr.else_branch())
+        }
+    }
+
     fn visit_grouping_expr(&mut self, expr: &Grouping) -> Result<LiteralType> {
         self.evaluate(expr.expression())
     }
 
+    fn visit_index_expr(&mut self, expr: &Index) -> Result<LiteralType> {
+        let object = self.evaluate(expr.object())?;
+        let index = self.evaluate(expr.index())?;
+        if let LiteralType::Map(map) = object {
+            let key = Self::map_key(index, expr.bracket())?;
+            return map
+                .borrow()
+                .get(&key)
+                .cloned()
+                .ok_or_else(|| InterError::NoKey {
+                    tk: expr.bracket().clone(),
+                    key: key.to_string(),
+                });
+        }
+        let (list, index) = Self::list_index(object, &index, expr.bracket())?;
+        Ok(list.borrow()[index].clone())
+    }
+
+    fn visit_list_expr(&mut self, expr: &List) -> Result<LiteralType> {
+        let mut elements = Vec::with_capacity(expr.elements().len());
+        for ele in expr.elements() {
+            elements.push(self.evaluate(ele)?);
+        }
+        Ok(LiteralType::List(Rc::new(RefCell::new(elements))))
+    }
+
+    fn visit_map_expr(&mut self, expr: &Map) -> Result<LiteralType> {
+        let mut map = IndexMap::with_capacity(expr.entries().len());
+        for (key, value) in expr.entries() {
+            let key = Self::map_key(self.evaluate(key)?, expr.brace())?;
+            let value = self.evaluate(value)?;
+            map.insert(key, value);
+        }
+        Ok(LiteralType::Map(Rc::new(RefCell::new(map))))
+    }
+
+    fn visit_lambda_expr(&mut self, expr: &Lambda) -> Result<LiteralType> {
+        let fun = LoxFunction::new(expr.function().clone(), Rc::clone(&self.environment), false);
+        Ok(LiteralType::Callable(Callables::Fun(fun)))
+    }
+
     fn visit_literal_expr(&mut self, expr: &Literal) -> Result<LiteralType> {
         Ok(expr.value().clone())
     }
@@ -453,7 +1074,16 @@ impl ExprVisitor<Result<LiteralType>> for Interpreter {
         let object = self.evaluate(expr.object())?;
         match object {
             LiteralType::LoxInstance(instance) => {
-                let value = self.evaluate(expr.value())?;
+                let value = match expr.operator() {
+                    // the object is evaluated only once
+                    Some(operator) => {
+                        let current = LoxInstance::get(&instance, expr.name(), self)?
+                            .ok_or_else(|| InterError::NoProperty(expr.name().clone()))?;
+                        let right = self.evaluate(expr.value())?;
+                        Self::binary_op(current, operator, right)?
+                    },
+                    None => self.evaluate(expr.value())?,
+                };
                 instance
                     .borrow_mut()
                     .set(expr.name().clone(), value.clone());
@@ -463,6 +1093,54 @@ impl ExprVisitor<Result<LiteralType>> for Interpreter {
         }
     }
 
+    fn visit_setindex_expr(&mut self, expr: &SetIndex) -> Result<LiteralType> {
+        // the object and index are evaluated only once
+        let object = self.evaluate(expr.object())?;
+        let index = self.evaluate(expr.index())?;
+        if let LiteralType::Map(map) = object {
+            let key = Self::map_key(index, expr.bracket())?;
+            let value = match expr.operator() {
+                Some(operator) => {
+                    let current =
+                        map.borrow()
+                            .get(&key)
+                            .cloned()
+                            .ok_or_else(|| InterError::NoKey {
+                                tk: expr.bracket().clone(),
+                                key: key.to_string(),
+                            })?;
+                    let right = self.evaluate(expr.value())?;
+                    Self::binary_op(current, operator, right)?
+                },
+                None => self.evaluate(expr.value())?,
+            };
+            map.borrow_mut().insert(key, value.clone());
+            return Ok(value);
+        }
+        let (list, index) = Self::list_index(object, &index, expr.bracket())?;
+        let value = match expr.operator() {
+            Some(operator) => {
+                let current = list.borrow()[index].clone();
+                let right = self.evaluate(expr.value())?;
+                Self::binary_op(current, operator, right)?
+            },
+            None => self.evaluate(expr.value())?,
+        };
+        // the value may have changed the length
+        let mut list = list.borrow_mut();
+        let len = list.len();
+        let slot = list
+            .get_mut(index)
+            .ok_or_else(|| InterError::IndexOutOfRange {
+                tk: expr.bracket().clone(),
+                #[expect(clippy::cast_precision_loss, reason = "lists are not that long")]
+                index: index as f64,
+                len,
+            })?;
+        *slot = value.clone();
+        Ok(value)
+    }
+
     fn visit_super_expr(&mut self, expr: &Super) -> Result<LiteralType> {
         let distance = *unsafe {
             self.locals
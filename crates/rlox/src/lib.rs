@@ -1,4 +1,4 @@
-#![feature(let_chains, try_blocks, duration_millis_float, coroutines, gen_blocks)]
+#![feature(try_blocks, duration_millis_float, coroutines, gen_blocks)]
 
 pub mod ast_printer;
 pub mod cli;
@@ -9,11 +9,15 @@ pub mod lox;
 pub mod lox_callable;
 pub mod lox_class;
 pub mod lox_fun;
+pub mod lox_generator;
 pub mod lox_instance;
+pub mod lox_module;
+pub mod lox_trait;
 pub mod parser;
 pub mod prompt;
 pub mod resolver;
 pub mod r#return;
 pub mod scan;
+pub mod span;
 pub mod stmt;
 pub mod token;
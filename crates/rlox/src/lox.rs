@@ -27,7 +27,8 @@ impl Lox {
     }
 
     pub fn run_file<T: AsRef<Path>>(mut self, path: T) -> Result<()> {
-        let content = std::fs::read_to_string(path)?;
+        let content = std::fs::read_to_string(&path)?;
+        self.interpreter.set_main_file(path.as_ref());
         self.run(&content, false)
     }
     pub fn ast_file<T: AsRef<Path>>(mut self, path: T) -> Result<()> {
@@ -37,12 +38,21 @@ impl Lox {
 
     pub fn run(&mut self, source: &str, ast: bool) -> Result<()> {
         let mut scanner = Scanner::new(source);
-        let tokens = scanner.scan_tokens();
+        let tokens: Vec<_> = scanner.scan_tokens().collect();
+        if !scanner.errors().is_empty() {
+            for e in scanner.errors() {
+                tracing::error!("{e}");
+            }
+            bail!("scan err")
+        }
 
         let mut parser = Parser::new(tokens);
-        let (mut expression, had_err) = parser.parse();
-        if had_err {
-            bail!("parse err")
+        let (mut expression, errors) = parser.parse();
+        if !errors.is_empty() {
+            for e in &errors {
+                tracing::error!("{e}");
+            }
+            bail!("parse err, found {} problems", errors.len())
         }
         let mut r = Resolver::new(&mut self.interpreter);
         let had_err = r.resolve(&expression);
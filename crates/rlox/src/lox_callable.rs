@@ -7,14 +7,50 @@ use crate::{
     expr::LiteralType,
     interpreter::{InterError, Interpreter},
     lox_class::LoxClass,
-    lox_fun::{ClockFunction, LoxFunction},
+    lox_fun::{ClockFunction, IterFunction, LoxFunction, NativeFunction, StrFunction},
+    lox_generator::GeneratorMethod,
 };
 
 pub type CallResult<T> = std::result::Result<T, InterError>;
 
 pub trait LoxCallable {
     fn call(&self, inter: &mut Interpreter, args: Vec<LiteralType>) -> CallResult<LiteralType>;
-    fn arity(&self) -> usize;
+    fn arity(&self) -> Arity;
+}
+
+/// How many arguments a callable takes, there is no `max` with a rest parameter
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+pub struct Arity {
+    pub min: usize,
+    pub max: Option<usize>,
+}
+
+impl Arity {
+    pub const fn exact(n: usize) -> Self {
+        Self {
+            min: n,
+            max: Some(n),
+        }
+    }
+
+    pub const fn contains(self, n: usize) -> bool {
+        match self.max {
+            Some(max) => self.min <= n && n <= max,
+            None => self.min <= n,
+        }
+    }
+}
+
+impl Display for Arity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.max {
+            Some(max) if max == self.min => write!(f, "{max}"),
+            Some(max) => write!(f, "{} to {max}", self.min),
+            None => write!(f, "at least {}", self.min),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -23,7 +59,11 @@ pub trait LoxCallable {
 pub enum Callables {
     Fun(LoxFunction),
     Clock(ClockFunction),
+    Str(StrFunction),
     Class(LoxClass),
+    Native(NativeFunction),
+    Iter(IterFunction),
+    Generator(GeneratorMethod),
 }
 
 impl Hash for Callables {
@@ -32,7 +72,11 @@ impl Hash for Callables {
         match self {
             Self::Fun(f0) => f0.hash(state),
             Self::Clock(f0) => f0.hash(state),
+            Self::Str(f0) => f0.hash(state),
             Self::Class(f0) => f0.hash(state),
+            Self::Native(f0) => f0.hash(state),
+            Self::Iter(f0) => f0.hash(state),
+            Self::Generator(f0) => f0.hash(state),
         }
     }
 }
@@ -42,7 +86,11 @@ impl Display for Callables {
         match self {
             Self::Fun(lox_function) => lox_function.fmt(f),
             Self::Clock(clock_function) => clock_function.fmt(f),
+            Self::Str(str_function) => str_function.fmt(f),
             Self::Class(lox_class) => lox_class.fmt(f),
+            Self::Native(native) => native.fmt(f),
+            Self::Iter(iter) => iter.fmt(f),
+            Self::Generator(method) => method.fmt(f),
         }
     }
 }
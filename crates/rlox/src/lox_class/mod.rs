@@ -1,12 +1,18 @@
 #[cfg(test)]
 mod tests;
 
-use std::{cell::RefCell, collections::HashMap, fmt::Display, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt::Display,
+    rc::Rc,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
 use crate::{
     expr::LiteralType,
     interpreter::Interpreter,
-    lox_callable::{CallResult, LoxCallable},
+    lox_callable::{Arity, CallResult, LoxCallable},
     lox_fun::LoxFunction,
     lox_instance::LoxInstance,
 };
@@ -14,19 +20,34 @@ use crate::{
 #[derive(Clone)]
 #[derive(Debug)]
 #[derive(Default)]
-#[derive(PartialEq, Eq)]
 pub struct LoxClass {
+    /// Tells classes apart, copies of one class value share it
+    id: usize,
     name: String,
     methods: HashMap<String, LoxFunction>,
-    superclass: Option<Box<LoxClass>>,
+    static_methods: HashMap<String, LoxFunction>,
+    getters: HashMap<String, LoxFunction>,
+    superclass: Option<Box<Self>>,
+}
+
+/// Every class declaration evaluated makes a new class, even with the same body
+static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
+
+thread_local! {
+    static ERROR: LoxClass = LoxClass::new("Error".to_owned(), None, HashMap::new());
 }
 
+impl PartialEq for LoxClass {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for LoxClass {}
+
 impl std::hash::Hash for LoxClass {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        self.name.hash(state);
-        for ele in &self.methods {
-            ele.hash(state);
-        }
+        self.id.hash(state);
     }
 }
 
@@ -39,8 +60,9 @@ impl LoxCallable for LoxClass {
         Ok(LiteralType::LoxInstance(Rc::clone(&instance)))
     }
 
-    fn arity(&self) -> usize {
-        self.find_method("init").map_or(0, |init| init.arity())
+    fn arity(&self) -> Arity {
+        self.find_method("init")
+            .map_or(Arity::exact(0), |init| init.arity())
     }
 }
 
@@ -51,18 +73,40 @@ impl Display for LoxClass {
 }
 
 impl LoxClass {
-    pub const fn new(
+    pub fn new(
         name: String,
         superclass: Option<Box<Self>>,
         methods: HashMap<String, LoxFunction>,
     ) -> Self {
         Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
             name,
             methods,
+            static_methods: HashMap::new(),
+            getters: HashMap::new(),
             superclass,
         }
     }
 
+    /// The built-in `Error`, caught runtime errors are its instances
+    pub fn error() -> Self {
+        ERROR.with(Clone::clone)
+    }
+
+    #[must_use]
+    pub fn with_static_methods(mut self, static_methods: HashMap<String, LoxFunction>) -> Self {
+        self.static_methods = static_methods;
+        self
+    }
+
+    #[must_use]
+    pub fn with_getters(mut self, getters: HashMap<String, LoxFunction>) -> Self {
+        self.getters = getters;
+        self
+    }
+
+    /// Resolution order: the class's own methods, then methods copied from its
+    /// traits, then the superclass chain
     pub fn find_method(&self, name: &str) -> Option<LoxFunction> {
         if let m @ Some(_) = self.methods.get(name) {
             return m.cloned();
@@ -72,4 +116,34 @@ impl LoxClass {
             .as_ref()
             .and_then(|sup| sup.find_method(name))
     }
+
+    /// `class fun` methods, looked up on the class value itself
+    pub fn find_static_method(&self, name: &str) -> Option<LoxFunction> {
+        if let m @ Some(_) = self.static_methods.get(name) {
+            return m.cloned();
+        }
+
+        self.superclass
+            .as_ref()
+            .and_then(|sup| sup.find_static_method(name))
+    }
+
+    pub fn find_getter(&self, name: &str) -> Option<LoxFunction> {
+        if let m @ Some(_) = self.getters.get(name) {
+            return m.cloned();
+        }
+
+        self.superclass
+            .as_ref()
+            .and_then(|sup| sup.find_getter(name))
+    }
+
+    /// It's `class` or a subclass of `class`
+    pub fn is_subclass_of(&self, class: &Self) -> bool {
+        self == class
+            || self
+                .superclass
+                .as_ref()
+                .is_some_and(|sup| sup.is_subclass_of(class))
+    }
 }
@@ -7,9 +7,32 @@ use super::LoxClass;
 #[test]
 fn class_display() {
     let lox_class = LoxClass {
+        id: 0,
         name: "test".to_owned(),
         methods: HashMap::new(),
+        static_methods: HashMap::new(),
+        getters: HashMap::new(),
         superclass: None,
     };
     assert_eq!(lox_class.to_string(), "test");
 }
+
+#[test]
+fn class_subclass_of() {
+    let shape = LoxClass::new("Shape".to_owned(), None, HashMap::new());
+    let point = LoxClass::new(
+        "Point".to_owned(),
+        Some(Box::new(shape.clone())),
+        HashMap::new(),
+    );
+    let other = LoxClass::new("Other".to_owned(), None, HashMap::new());
+    assert!(point.is_subclass_of(&point));
+    assert!(point.is_subclass_of(&shape));
+    assert!(!shape.is_subclass_of(&point));
+    assert!(!point.is_subclass_of(&other));
+
+    let twin = LoxClass::new("Shape".to_owned(), None, HashMap::new());
+    assert!(!twin.is_subclass_of(&shape));
+    assert!(!point.is_subclass_of(&twin));
+    assert!(LoxClass::error().is_subclass_of(&LoxClass::error()));
+}
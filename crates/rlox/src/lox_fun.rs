@@ -4,9 +4,11 @@ use crate::{
     env::Environment,
     expr::LiteralType,
     interpreter::{InterError, Interpreter},
-    lox_callable::LoxCallable,
+    lox_callable::{Arity, LoxCallable},
+    lox_generator::LoxGenerator,
     lox_instance::LoxInstance,
     stmt::Function,
+    token::Token,
 };
 
 type Result<T> = std::result::Result<T, InterError>;
@@ -54,8 +56,29 @@ impl LoxFunction {
 impl LoxCallable for LoxFunction {
     fn call(&self, inter: &mut Interpreter, args: Vec<LiteralType>) -> Result<LiteralType> {
         let env = Environment::with_enclosing(Rc::clone(&self.closure));
-        for (tk, val) in self.declaration.params.iter().zip(args.iter()) {
-            env.define(tk.lexeme().to_owned(), val.clone());
+        let mut args = args.into_iter();
+        for (idx, tk) in self.declaration.params.iter().enumerate() {
+            let val = match args.next() {
+                Some(val) => val,
+                // evaluated now, it can see the params before it
+                None => match self.declaration.default_of(idx) {
+                    Some(default) => inter.evaluate_in(default, env.clone())?,
+                    None => LiteralType::Nil,
+                },
+            };
+            env.define(tk.lexeme().to_owned(), val);
+        }
+        if let Some(rest) = &self.declaration.rest {
+            let rest_args = LiteralType::List(Rc::new(RefCell::new(args.collect())));
+            env.define(rest.lexeme().to_owned(), rest_args);
+        }
+        if self.declaration.is_generator {
+            let name = match &self.declaration.name {
+                Token::Fun { .. } => "anonymous",
+                name => name.lexeme(),
+            };
+            let generator = LoxGenerator::new(name.to_owned(), self.declaration.body.clone(), env);
+            return Ok(LiteralType::Generator(Rc::new(generator)));
         }
         match inter.execute_block(&self.declaration.body, env) {
             Ok(()) => {},
@@ -76,14 +99,21 @@ impl LoxCallable for LoxFunction {
         Ok(LiteralType::Nil)
     }
 
-    fn arity(&self) -> usize {
-        self.declaration.params.len()
+    fn arity(&self) -> Arity {
+        let params = self.declaration.params.len();
+        Arity {
+            min: params - self.declaration.defaults.len(),
+            max: self.declaration.rest.is_none().then_some(params),
+        }
     }
 }
 
 impl std::fmt::Display for LoxFunction {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        format!("<fn {}>", self.declaration.name.lexeme()).fmt(f)
+        match &self.declaration.name {
+            Token::Fun { .. } => "<fn anonymous>".fmt(f),
+            name => format!("<fn {}>", name.lexeme()).fmt(f),
+        }
     }
 }
 
@@ -110,7 +140,265 @@ impl LoxCallable for ClockFunction {
         Ok(LiteralType::Number(now.as_millis_f64()))
     }
 
-    fn arity(&self) -> usize {
-        0
+    fn arity(&self) -> Arity {
+        Arity::exact(0)
+    }
+}
+
+/// Converts any value to its printed string, used by string interpolation
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub struct StrFunction;
+
+impl Display for StrFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("<fn str>(inner)")
+    }
+}
+
+impl LoxCallable for StrFunction {
+    fn call(&self, _inter: &mut Interpreter, args: Vec<LiteralType>) -> Result<LiteralType> {
+        let value = args.first().map_or_else(String::new, ToString::to_string);
+        Ok(LiteralType::String(value))
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exact(1)
+    }
+}
+
+/// Built-in functions over lists and maps
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+#[derive(PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub enum NativeFunction {
+    /// `len(xs)`, the length of a list, map or string
+    Len,
+    /// `push(xs, v)`, append `v` and return it
+    Push,
+    /// `pop(xs)`, remove and return the last element
+    Pop,
+    /// `keys(m)`, a list of the keys in insertion order
+    Keys,
+    /// `values(m)`, a list of the values in insertion order
+    Values,
+    /// `has(m, k)`, whether the key is in the map
+    Has,
+    /// `remove(m, k)`, remove the key and return its value, or `nil`
+    Remove,
+}
+
+impl NativeFunction {
+    pub const ALL: [Self; 7] = [
+        Self::Len,
+        Self::Push,
+        Self::Pop,
+        Self::Keys,
+        Self::Values,
+        Self::Has,
+        Self::Remove,
+    ];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Len => "len",
+            Self::Push => "push",
+            Self::Pop => "pop",
+            Self::Keys => "keys",
+            Self::Values => "values",
+            Self::Has => "has",
+            Self::Remove => "remove",
+        }
+    }
+
+    /// Run it, the caller locates the error message
+    pub fn apply(self, args: Vec<LiteralType>) -> std::result::Result<LiteralType, String> {
+        let mut args = args.into_iter();
+        let first = args.next().unwrap_or(LiteralType::Nil);
+        match (self, first) {
+            #[expect(clippy::cast_precision_loss, reason = "lists are not that long")]
+            (Self::Len, LiteralType::List(list)) => {
+                Ok(LiteralType::Number(list.borrow().len() as f64))
+            },
+            #[expect(clippy::cast_precision_loss, reason = "maps are not that long")]
+            (Self::Len, LiteralType::Map(map)) => {
+                Ok(LiteralType::Number(map.borrow().len() as f64))
+            },
+            #[expect(clippy::cast_precision_loss, reason = "strings are not that long")]
+            (Self::Len, LiteralType::String(s)) => {
+                Ok(LiteralType::Number(s.chars().count() as f64))
+            },
+            (Self::Push, LiteralType::List(list)) => {
+                let value = args.next().unwrap_or(LiteralType::Nil);
+                list.borrow_mut().push(value.clone());
+                Ok(value)
+            },
+            (Self::Pop, LiteralType::List(list)) => list
+                .borrow_mut()
+                .pop()
+                .ok_or_else(|| "Can't pop from an empty list".to_owned()),
+            (Self::Keys, LiteralType::Map(map)) => {
+                let keys = map.borrow().keys().cloned().collect();
+                Ok(LiteralType::List(Rc::new(RefCell::new(keys))))
+            },
+            (Self::Values, LiteralType::Map(map)) => {
+                let values = map.borrow().values().cloned().collect();
+                Ok(LiteralType::List(Rc::new(RefCell::new(values))))
+            },
+            (Self::Has, LiteralType::Map(map)) => {
+                let has = args
+                    .next()
+                    .and_then(LiteralType::into_key)
+                    .is_some_and(|key| map.borrow().contains_key(&key));
+                Ok(LiteralType::Bool(has))
+            },
+            (Self::Remove, LiteralType::Map(map)) => {
+                let removed = args
+                    .next()
+                    .and_then(LiteralType::into_key)
+                    .and_then(|key| map.borrow_mut().shift_remove(&key));
+                Ok(removed.unwrap_or(LiteralType::Nil))
+            },
+            (Self::Len, _) => Err("Operand of `len` must be a list, map or string".to_owned()),
+            (Self::Push | Self::Pop, _) => {
+                Err(format!("First operand of `{}` must be a list", self.name()))
+            },
+            (..) => Err(format!("First operand of `{}` must be a map", self.name())),
+        }
+    }
+}
+
+impl Display for NativeFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<fn {}>(inner)", self.name())
+    }
+}
+
+impl LoxCallable for NativeFunction {
+    fn call(&self, _inter: &mut Interpreter, args: Vec<LiteralType>) -> Result<LiteralType> {
+        self.apply(args).map_err(InterError::Message)
+    }
+
+    fn arity(&self) -> Arity {
+        match self {
+            Self::Len | Self::Pop | Self::Keys | Self::Values => Arity::exact(1),
+            Self::Push | Self::Has | Self::Remove => Arity::exact(2),
+        }
+    }
+}
+
+/// The cursor over a built-in sequence in a `for (var x in xs)` loop,
+/// the items are copied when the loop starts
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq, Hash)]
+pub struct SeqIter {
+    items: Vec<LiteralType>,
+    index: usize,
+}
+
+/// The steps of a desugared `for (var x in xs)`, not reachable by name
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+#[derive(PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub enum IterFunction {
+    /// Get the iterator: a cursor over a list, map keys or string chars, a generator,
+    /// or what the `iter()` method of an instance returns
+    Iter,
+    /// Call `done()` of the iterator
+    Done,
+    /// Call `next()` of the iterator
+    Next,
+}
+
+impl IterFunction {
+    /// `tk` is the `in` of the loop, where errors are reported
+    pub fn apply(
+        self,
+        inter: &mut Interpreter,
+        iterable: LiteralType,
+        tk: &Token,
+    ) -> Result<LiteralType> {
+        match (self, iterable) {
+            (Self::Iter, LiteralType::LoxInstance(instance)) => {
+                let iter = Self::call_method(inter, &instance, "iter", tk)?;
+                match iter {
+                    // a custom iterator
+                    LiteralType::LoxInstance(_) => Ok(iter),
+                    // or a built-in sequence to walk
+                    seq => Self::seq_iter(seq, tk),
+                }
+            },
+            (Self::Iter, generator @ LiteralType::Generator(_)) => Ok(generator),
+            (Self::Iter, seq) => Self::seq_iter(seq, tk),
+            (Self::Done, LiteralType::SeqIter(seq)) => {
+                let seq = seq.borrow();
+                Ok(LiteralType::Bool(seq.index >= seq.items.len()))
+            },
+            (Self::Next, LiteralType::SeqIter(seq)) => {
+                let mut seq = seq.borrow_mut();
+                let item = seq.items.get(seq.index).cloned();
+                seq.index += 1;
+                Ok(item.unwrap_or(LiteralType::Nil))
+            },
+            (Self::Done, LiteralType::LoxInstance(instance)) => {
+                Self::call_method(inter, &instance, "done", tk)
+            },
+            (Self::Next, LiteralType::LoxInstance(instance)) => {
+                Self::call_method(inter, &instance, "next", tk)
+            },
+            (Self::Done, LiteralType::Generator(generator)) => {
+                Ok(LiteralType::Bool(generator.done(inter)?))
+            },
+            (Self::Next, LiteralType::Generator(generator)) => generator.next(inter),
+            (Self::Done | Self::Next, _) => Err(InterError::NotIterable(tk.clone())),
+        }
+    }
+
+    fn seq_iter(seq: LiteralType, tk: &Token) -> Result<LiteralType> {
+        let items = match seq {
+            LiteralType::List(list) => list.borrow().clone(),
+            LiteralType::Map(map) => map.borrow().keys().cloned().collect(),
+            LiteralType::String(s) => s
+                .chars()
+                .map(|c| LiteralType::String(c.to_string()))
+                .collect(),
+            _ => return Err(InterError::NotIterable(tk.clone())),
+        };
+        Ok(LiteralType::SeqIter(Rc::new(RefCell::new(SeqIter {
+            items,
+            index: 0,
+        }))))
+    }
+
+    fn call_method(
+        inter: &mut Interpreter,
+        instance: &Rc<RefCell<LoxInstance>>,
+        name: &'static str,
+        tk: &Token,
+    ) -> Result<LiteralType> {
+        let method = LoxInstance::method(instance, name).ok_or_else(|| InterError::Iterator {
+            tk: tk.clone(),
+            method: name,
+        })?;
+        if !method.arity().contains(0) {
+            return Err(InterError::ArgsArity {
+                tk: tk.clone(),
+                expect: method.arity(),
+                actual: 0,
+            });
+        }
+        method.call(inter, vec![])
+    }
+}
+
+impl Display for IterFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Iter => f.write_str("<fn iter>(inner)"),
+            Self::Done => f.write_str("<fn done>(inner)"),
+            Self::Next => f.write_str("<fn next>(inner)"),
+        }
     }
 }
@@ -0,0 +1,425 @@
+use std::{
+    cell::RefCell,
+    fmt::{self, Display},
+    rc::Rc,
+};
+
+use crate::{
+    env::Environment,
+    expr::LiteralType,
+    interpreter::{InterError, Interpreter},
+    lox_callable::{Arity, Callables, LoxCallable},
+    r#return::FnReturn,
+    stmt::{Stmts, Try, While},
+    token::Token,
+};
+
+type Result<T> = std::result::Result<T, InterError>;
+
+/// What a call to a function with `yield` returns, its body runs up to the next `yield` on
+/// `next()` or `done()`, `close()` finishes it early
+#[derive(Debug)]
+pub struct LoxGenerator {
+    name: String,
+    state: RefCell<GeneratorState>,
+}
+
+#[derive(Debug)]
+struct GeneratorState {
+    /// Where the body goes on, innermost last, empty once it has finished
+    frames: Vec<Frame>,
+    /// Run ahead by `done()`, handed out by the next `next()`
+    peeked: Option<LiteralType>,
+}
+
+/// A statement with a `yield` in it, part way through
+#[derive(Debug)]
+enum Frame {
+    /// The statements of a block or a branch, run in `env`
+    Block {
+        stmts: Vec<Stmts>,
+        next: usize,
+        env: Environment,
+    },
+    /// A `while`, its condition is checked each time the frame is on top
+    Loop {
+        stmt: Box<While>,
+        env: Environment,
+        started: bool,
+    },
+    /// A `try`, the clause it is in runs as a block above it
+    Try {
+        stmt: Box<Try>,
+        env: Environment,
+        stage: TryStage,
+    },
+}
+
+#[derive(Debug)]
+enum TryStage {
+    Body,
+    Catch,
+    /// What goes on after the `finally`, `None` when the clause before it finished
+    Finally(Option<InterError>),
+}
+
+// each call makes a new generator
+impl PartialEq for LoxGenerator {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl Eq for LoxGenerator {}
+
+impl std::hash::Hash for LoxGenerator {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+    }
+}
+
+impl Display for LoxGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<generator {}>", self.name)
+    }
+}
+
+impl LoxGenerator {
+    /// `env` holds the arguments, the body runs in it as a function body does
+    pub fn new(name: String, body: Vec<Stmts>, env: Environment) -> Self {
+        Self {
+            name,
+            state: RefCell::new(GeneratorState {
+                frames: vec![Frame::Block {
+                    stmts: body,
+                    next: 0,
+                    env,
+                }],
+                peeked: None,
+            }),
+        }
+    }
+
+    /// `gen.next`, `gen.done` and `gen.close`
+    pub fn get(generator: &Rc<Self>, name: &Token) -> Option<LiteralType> {
+        let step = match name.lexeme() {
+            "next" => GeneratorStep::Next,
+            "done" => GeneratorStep::Done,
+            "close" => GeneratorStep::Close,
+            _ => return None,
+        };
+        Some(LiteralType::Callable(Callables::Generator(
+            GeneratorMethod {
+                generator: Rc::clone(generator),
+                step,
+            },
+        )))
+    }
+
+    /// The next yielded value, `nil` once the body has finished
+    pub fn next(&self, inter: &mut Interpreter) -> Result<LiteralType> {
+        let mut state = self.running()?;
+        if let Some(value) = state.peeked.take() {
+            return Ok(value);
+        }
+        Ok(state.resume(inter)?.unwrap_or(LiteralType::Nil))
+    }
+
+    /// Whether the body has finished, it runs ahead to the next `yield` to know
+    pub fn done(&self, inter: &mut Interpreter) -> Result<bool> {
+        let mut state = self.running()?;
+        if state.peeked.is_none() {
+            state.peeked = state.resume(inter)?;
+        }
+        Ok(state.peeked.is_none())
+    }
+
+    /// Finishes the body as a `return` at the `yield` would, running the `finally` it is in
+    pub fn close(&self, inter: &mut Interpreter) -> Result<()> {
+        self.running()?.close(inter)
+    }
+
+    fn running(&self) -> Result<std::cell::RefMut<'_, GeneratorState>> {
+        self.state
+            .try_borrow_mut()
+            .map_err(|_| InterError::Message(format!("{self} is already running")))
+    }
+}
+
+impl GeneratorState {
+    /// Runs to the next `yield`, `None` when the body finishes
+    fn resume(&mut self, inter: &mut Interpreter) -> Result<Option<LiteralType>> {
+        self.run(inter, None)
+    }
+
+    fn close(&mut self, inter: &mut Interpreter) -> Result<()> {
+        self.peeked = None;
+        let stop = InterError::Return(Box::new(FnReturn::new(LiteralType::Nil)));
+        if self.run(inter, Some(stop))?.is_some() {
+            // a `yield` in `finally`, nothing is left to take it
+            self.frames.clear();
+        }
+        Ok(())
+    }
+
+    /// Goes on from the top frame, or unwinds `raised` from it first
+    fn run(
+        &mut self,
+        inter: &mut Interpreter,
+        mut raised: Option<InterError>,
+    ) -> Result<Option<LiteralType>> {
+        loop {
+            if let Some(e) = raised.take() {
+                self.unwind(e).inspect_err(|_| self.frames.clear())?;
+            }
+            let Some(frame) = self.frames.last_mut()
+            else {
+                break;
+            };
+            let res = match frame {
+                Frame::Block { stmts, next, env } => {
+                    let Some(stmt) = stmts.get(*next).cloned()
+                    else {
+                        self.frames.pop();
+                        continue;
+                    };
+                    *next += 1;
+                    let env = env.clone();
+                    self.step(inter, &stmt, env)
+                },
+                Frame::Loop { stmt, env, started } => {
+                    let first = !std::mem::replace(started, true);
+                    let (stmt, env) = (stmt.clone(), env.clone());
+                    self.iterate(inter, &stmt, env, first)
+                },
+                Frame::Try { .. } => self.finish_clause().map(|()| None),
+            };
+            match res {
+                Ok(None) => {},
+                Ok(Some(value)) => return Ok(Some(value)),
+                Err(e) => raised = Some(e),
+            }
+        }
+        Ok(None)
+    }
+
+    /// Runs `stmt` in `env`, one with a `yield` in it becomes a frame to go through
+    fn step(
+        &mut self,
+        inter: &mut Interpreter,
+        stmt: &Stmts,
+        env: Environment,
+    ) -> Result<Option<LiteralType>> {
+        match stmt {
+            Stmts::Yield(yield_) => {
+                let value = match yield_.value() {
+                    Some(value) => inter.evaluate_in(value, env)?,
+                    None => LiteralType::Nil,
+                };
+                return Ok(Some(value));
+            },
+            Stmts::Block(block) if stmt.has_yield() => {
+                self.frames.push(Frame::block(block.statements(), &env));
+            },
+            Stmts::If(if_) if stmt.has_yield() => {
+                let cond = inter.evaluate_in(if_.condition(), env.clone())?;
+                let branch = if Interpreter::is_truthy(&cond) {
+                    Some(if_.then_branch())
+                }
+                else {
+                    if_.else_branch()
+                };
+                if let Some(branch) = branch {
+                    self.frames.push(Frame::Block {
+                        stmts: vec![branch.clone()],
+                        next: 0,
+                        env,
+                    });
+                }
+            },
+            Stmts::While(while_) if stmt.has_yield() => self.frames.push(Frame::Loop {
+                stmt: Box::new(while_.clone()),
+                env,
+                started: false,
+            }),
+            Stmts::Match(match_) if stmt.has_yield() => {
+                let value = inter.evaluate_in(match_.value(), env.clone())?;
+                let arm = inter.with_env(env.clone(), |inter| inter.match_arm(match_, &value))?;
+                match arm {
+                    Some((body, None)) => self.frames.push(Frame::Block {
+                        stmts: vec![body.clone()],
+                        next: 0,
+                        env,
+                    }),
+                    Some((body, Some(binding))) => {
+                        let mut frame = Frame::block(std::slice::from_ref(body), &env);
+                        frame.define(binding, value);
+                        self.frames.push(frame);
+                    },
+                    None => {},
+                }
+            },
+            Stmts::Try(try_) if stmt.has_yield() => {
+                let body = Frame::block(try_.body(), &env);
+                self.frames.push(Frame::Try {
+                    stmt: Box::new(try_.clone()),
+                    env,
+                    stage: TryStage::Body,
+                });
+                self.frames.push(body);
+            },
+            _ => inter.execute_block(std::slice::from_ref(stmt), env)?,
+        }
+        Ok(None)
+    }
+
+    /// One pass of a loop on top, the increment runs before every condition but the first
+    fn iterate(
+        &mut self,
+        inter: &mut Interpreter,
+        stmt: &While,
+        env: Environment,
+        first: bool,
+    ) -> Result<Option<LiteralType>> {
+        if !first && let Some(increment) = stmt.increment() {
+            inter.evaluate_in(increment, env.clone())?;
+        }
+        if Interpreter::is_truthy(&inter.evaluate_in(stmt.condition(), env.clone())?) {
+            return self.step(inter, stmt.body(), env);
+        }
+        self.frames.pop();
+        Ok(None)
+    }
+
+    /// A clause of the `try` on top has finished, its `finally` runs next, then what was
+    /// pending before it goes on
+    fn finish_clause(&mut self) -> Result<()> {
+        let Some(Frame::Try { stmt, env, stage }) = self.frames.last_mut()
+        else {
+            return Ok(());
+        };
+        if let TryStage::Finally(pending) = stage {
+            let pending = pending.take();
+            self.frames.pop();
+            return pending.map_or(Ok(()), Err);
+        }
+        let Some(finally) = stmt.finally()
+        else {
+            self.frames.pop();
+            return Ok(());
+        };
+        let finally = Frame::block(finally, env);
+        *stage = TryStage::Finally(None);
+        self.frames.push(finally);
+        Ok(())
+    }
+
+    /// Drops frames until one takes `e` as the interpreter would: a loop takes `break` and
+    /// `continue`, a `try` its `catch` and `finally`. A `return` finishes the body
+    fn unwind(&mut self, e: InterError) -> Result<()> {
+        while let Some(frame) = self.frames.last_mut() {
+            match frame {
+                // an error or a jump out of `finally` replaces the pending one
+                Frame::Block { .. }
+                | Frame::Try {
+                    stage: TryStage::Finally(_),
+                    ..
+                } => {},
+                Frame::Loop { .. } => match e {
+                    InterError::NeedBreak(_) => {
+                        self.frames.pop();
+                        return Ok(());
+                    },
+                    InterError::NeedContinue(_) => return Ok(()),
+                    _ => {},
+                },
+                Frame::Try { stmt, env, stage } => {
+                    if matches!(stage, TryStage::Body)
+                        && !e.is_unwinding()
+                        && let Some(catch) = stmt.catch()
+                    {
+                        let mut clause = Frame::block(&catch.body, env);
+                        clause.define(&catch.binding, e.into_value());
+                        *stage = TryStage::Catch;
+                        self.frames.push(clause);
+                        return Ok(());
+                    }
+                    if let Some(finally) = stmt.finally() {
+                        let clause = Frame::block(finally, env);
+                        *stage = TryStage::Finally(Some(e));
+                        self.frames.push(clause);
+                        return Ok(());
+                    }
+                },
+            }
+            self.frames.pop();
+        }
+        match e {
+            // `return` finishes the generator, the value is dropped
+            InterError::Return(_) => Ok(()),
+            e => Err(e),
+        }
+    }
+}
+
+impl Frame {
+    /// `stmts` in a new scope inside `env`
+    fn block(stmts: &[Stmts], env: &Environment) -> Self {
+        Self::Block {
+            stmts: stmts.to_vec(),
+            next: 0,
+            env: Environment::with_enclosing(Rc::new(RefCell::new(env.clone()))),
+        }
+    }
+
+    fn define(&mut self, name: &Token, value: LiteralType) {
+        if let Self::Block { env, .. } = self {
+            env.define(name.lexeme().to_owned(), value);
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq, Hash)]
+pub enum GeneratorStep {
+    Next,
+    Done,
+    Close,
+}
+
+/// `next()`, `done()` or `close()` of a generator
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq, Hash)]
+pub struct GeneratorMethod {
+    generator: Rc<LoxGenerator>,
+    step: GeneratorStep,
+}
+
+impl Display for GeneratorMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.step {
+            GeneratorStep::Next => write!(f, "<fn next>({})", self.generator),
+            GeneratorStep::Done => write!(f, "<fn done>({})", self.generator),
+            GeneratorStep::Close => write!(f, "<fn close>({})", self.generator),
+        }
+    }
+}
+
+impl LoxCallable for GeneratorMethod {
+    fn call(&self, inter: &mut Interpreter, _args: Vec<LiteralType>) -> Result<LiteralType> {
+        match self.step {
+            GeneratorStep::Next => self.generator.next(inter),
+            GeneratorStep::Done => Ok(LiteralType::Bool(self.generator.done(inter)?)),
+            GeneratorStep::Close => {
+                self.generator.close(inter)?;
+                Ok(LiteralType::Nil)
+            },
+        }
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exact(0)
+    }
+}
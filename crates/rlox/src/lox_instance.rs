@@ -1,6 +1,13 @@
 use std::{cell::RefCell, collections::HashMap, fmt::Display, rc::Rc};
 
-use crate::{expr::LiteralType, lox_class::LoxClass, lox_fun::LoxFunction, token::Token};
+use crate::{
+    expr::LiteralType,
+    interpreter::{InterError, Interpreter},
+    lox_callable::LoxCallable,
+    lox_class::LoxClass,
+    lox_fun::LoxFunction,
+    token::Token,
+};
 
 #[derive(Clone)]
 #[derive(Debug)]
@@ -35,20 +42,46 @@ impl LoxInstance {
             fields: HashMap::new(),
         }
     }
-    pub fn get(&self, name: &Token) -> Option<LiteralType> {
-        if let m @ Some(_) = self.fields.get(name.lexeme()) {
-            return m.cloned();
+    pub const fn class(&self) -> &LoxClass {
+        &self.klass
+    }
+
+    /// Methods are bound to the instance itself, so `this` sees the same fields
+    ///
+    /// Lookup order is fields, getters, then methods; a getter runs right away
+    pub fn get(
+        instance: &Rc<RefCell<Self>>,
+        name: &Token,
+        inter: &mut Interpreter,
+    ) -> Result<Option<LiteralType>, InterError> {
+        if let m @ Some(_) = instance.borrow().fields.get(name.lexeme()) {
+            return Ok(m.cloned());
         }
 
-        let method: Option<LoxFunction> = self.klass.find_method(name.lexeme());
+        // the borrow must end before the getter body touches `this`
+        let getter = instance.borrow().klass.find_getter(name.lexeme());
+        if let Some(getter) = getter {
+            return getter
+                .bind(Rc::clone(instance))
+                .call(inter, vec![])
+                .map(Some);
+        }
 
-        method.map(|m| {
-            let fun = m.bind(Rc::new(RefCell::new(self.to_owned())));
-            LiteralType::Callable(crate::lox_callable::Callables::Fun(fun))
-        })
+        Ok(Self::method(instance, name.lexeme())
+            .map(|fun| LiteralType::Callable(crate::lox_callable::Callables::Fun(fun))))
+    }
+
+    /// The method bound to the instance, fields are skipped
+    pub fn method(instance: &Rc<RefCell<Self>>, name: &str) -> Option<LoxFunction> {
+        let method: Option<LoxFunction> = instance.borrow().klass.find_method(name);
+        method.map(|m| m.bind(Rc::clone(instance)))
     }
 
     pub fn set(&mut self, name: Token, value: LiteralType) {
-        self.fields.insert(name.into_inner().lexeme_owned(), value);
+        self.set_field(name.into_inner().lexeme_owned(), value);
+    }
+
+    pub fn set_field(&mut self, name: String, value: LiteralType) {
+        self.fields.insert(name, value);
     }
 }
@@ -0,0 +1,39 @@
+use std::fmt::Display;
+
+use crate::{env::Environment, expr::LiteralType, token::Token};
+
+/// The top-level environment of an imported file, its names are reached with `m.name`
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+pub struct LoxModule {
+    path: String,
+    env: Environment,
+}
+
+// a module is loaded once per path
+impl std::hash::Hash for LoxModule {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.path.hash(state);
+    }
+}
+
+impl Display for LoxModule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<module {}>", self.path)
+    }
+}
+
+impl LoxModule {
+    pub const fn new(path: String, env: Environment) -> Self {
+        Self { path, env }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn get(&self, name: &Token) -> Option<LiteralType> {
+        self.env.get(name)
+    }
+}
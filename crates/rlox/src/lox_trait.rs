@@ -0,0 +1,42 @@
+use std::{collections::HashMap, fmt::Display};
+
+use crate::lox_fun::LoxFunction;
+
+/// Methods shared by unrelated classes, copied into every class that uses the trait
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(PartialEq, Eq)]
+pub struct LoxTrait {
+    name: String,
+    methods: HashMap<String, LoxFunction>,
+}
+
+impl std::hash::Hash for LoxTrait {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        for ele in &self.methods {
+            ele.hash(state);
+        }
+    }
+}
+
+impl Display for LoxTrait {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<trait {}>", self.name)
+    }
+}
+
+impl LoxTrait {
+    pub const fn new(name: String, methods: HashMap<String, LoxFunction>) -> Self {
+        Self { name, methods }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub const fn methods(&self) -> &HashMap<String, LoxFunction> {
+        &self.methods
+    }
+}
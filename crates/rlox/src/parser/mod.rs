@@ -8,8 +8,15 @@ use thiserror::Error;
 
 use crate::{
     expr::*,
-    stmt::{Block, Break, Class, Expression, Function, If, Print, Return, Stmts, Var, While},
-    token::Token,
+    lox_callable::Callables,
+    lox_fun::{IterFunction, StrFunction},
+    span::Span,
+    stmt::{
+        Block, Break, CatchClause, Class, Continue, Destructure, Expression, Function, If, Import,
+        Match, MatchArm, Pattern, Print, Return, Stmts, Throw, Trait, Try, Unpack, Var, While,
+        Yield,
+    },
+    token::{Source, Token, TokenInner},
 };
 
 #[derive(Clone)]
@@ -23,26 +30,54 @@ pub enum ParserError {
     RightParen(Token),
     #[error("Missing '}}' after expression: {0}")]
     RightBrace(Token),
-    #[error("End of source code, no next token: {0}")]
-    Eof(String),
+    #[error("Missing ']' after expression: {0}")]
+    RightBracket(Token),
+    #[error("End of source code, {msg}: {at}")]
+    Eof { msg: String, at: Token },
     #[error("Invalid Primary: {0}")]
     Primary(Token),
+    #[error("Expect `}}` after interpolated expression: {0}")]
+    Interpolation(Token),
     #[error("Expect `;` at stmt end: {0}")]
     PrintStmt(Token),
-    #[error("{0}")]
-    DeclarationStmt(String),
     #[error("Expect var name: {0}")]
     VarDeclaration(Token),
     #[error("Expect `;` after variable declaration: {0}")]
     Semicolon(Token),
+    #[error("Expect `:` after then branch of conditional expression: {0}")]
+    Colon(Token),
+    #[error("Expect `:` after map key: {0}")]
+    MapColon(Token),
+    #[error("Expect a literal, `Class name` or `_` pattern: {0}")]
+    Pattern(Token),
+    #[error("Expect `=>` after pattern: {0}")]
+    FatArrow(Token),
+    #[error("Parameter without a default after one with a default: {0}")]
+    DefaultParam(Token),
+    #[error("The rest parameter must be the last one: {0}")]
+    RestParam(Token),
+    #[error("Expect `catch` or `finally` after the try block: {0}")]
+    TryHandler(Token),
+    #[error("Expect a name for the caught error: {0}")]
+    CatchBinding(Token),
+    #[error("Expect a module path string: {0}")]
+    ImportPath(Token),
+    #[error("Expect `as` and a module name after the path: {0}")]
+    ImportAs(Token),
+    #[error("Expect `=` and a value after const name: {0}")]
+    ConstInit(Token),
+    #[error("Expect a name, `,` or the end of the pattern: {0}")]
+    Destructure(Token),
+    #[error("Expect `=` and a value to destructure: {0}")]
+    DestructureInit(Token),
+    #[error("Can't assign to const variable: {0}")]
+    AssignConst(Token),
     #[error("Invalid assignment target: {0}")]
     Assign(Token),
-    #[error("Must be inside a loop to use `break`")]
+    #[error("Must be inside a loop to use `break` or `continue`: {0}")]
     NotInLoop(Token),
     #[error("Can't have more than 255 arguments: {0}")]
     TooManyArgs(Token),
-    #[error("{0}")]
-    ErrMessage(String),
     #[error("Expect {kind} name: {tk}")]
     CallDecl { tk: Token, kind: FunctionKind },
     #[error("Expect parameters name: {0}")]
@@ -51,6 +86,8 @@ pub enum ParserError {
     Class(Token),
     #[error("Expect superclass name: {0}")]
     Superclass(Token),
+    #[error("Expect trait name: {0}")]
+    Trait(Token),
     #[error("A Class can't inherit from itself: {0}")]
     RecurseClass(Token),
     #[error("Can't read local variable in its own initializer: {0}")]
@@ -69,6 +106,62 @@ pub enum ParserError {
     ClassNoSuper(Token),
     #[error("Can't return a value from an initializer: {0}")]
     RtValInit(Token),
+    #[error("Use `yield` outer function: {0}")]
+    YieldNotInFn(Token),
+    #[error("Can't yield from an initializer: {0}")]
+    YieldInInit(Token),
+}
+
+impl ParserError {
+    /// Where the error is in the source
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Self::LeftParen(tk)
+            | Self::LeftBrace(tk)
+            | Self::RightParen(tk)
+            | Self::RightBrace(tk)
+            | Self::RightBracket(tk)
+            | Self::Primary(tk)
+            | Self::Interpolation(tk)
+            | Self::PrintStmt(tk)
+            | Self::VarDeclaration(tk)
+            | Self::Semicolon(tk)
+            | Self::Colon(tk)
+            | Self::MapColon(tk)
+            | Self::Pattern(tk)
+            | Self::FatArrow(tk)
+            | Self::DefaultParam(tk)
+            | Self::RestParam(tk)
+            | Self::TryHandler(tk)
+            | Self::CatchBinding(tk)
+            | Self::ImportPath(tk)
+            | Self::ImportAs(tk)
+            | Self::ConstInit(tk)
+            | Self::Destructure(tk)
+            | Self::DestructureInit(tk)
+            | Self::AssignConst(tk)
+            | Self::Assign(tk)
+            | Self::NotInLoop(tk)
+            | Self::TooManyArgs(tk)
+            | Self::CallDecl { tk, .. }
+            | Self::Parameters(tk)
+            | Self::Class(tk)
+            | Self::Superclass(tk)
+            | Self::Trait(tk)
+            | Self::RecurseClass(tk)
+            | Self::Initialization(tk)
+            | Self::DoubleVar(tk)
+            | Self::NotInFn(tk)
+            | Self::NotInClassThis(tk)
+            | Self::NotInClassSuper(tk)
+            | Self::ClassNoSuper(tk)
+            | Self::RtValInit(tk)
+            | Self::YieldNotInFn(tk)
+            | Self::YieldInInit(tk)
+            | Self::Eof { at: tk, .. } => Some(tk.into()),
+            Self::NotInScope => None,
+        }
+    }
 }
 
 pub type Result<T, E = ParserError> = core::result::Result<T, E>;
@@ -98,6 +191,11 @@ where
 {
     peeks: PeekNth<I>,
     loop_depth: usize,
+    /// end byte offset of the last consumed token
+    prev_end: usize,
+    /// source of the consumed tokens
+    origin: Source,
+    errors: Vec<ParserError>,
 }
 
 impl<I> Parser<I>
@@ -112,47 +210,92 @@ where
         Self {
             peeks,
             loop_depth: 0,
+            prev_end: 0,
+            origin: Source::default(),
+            errors: Vec::new(),
         }
     }
 
-    pub fn parse(&mut self) -> (Vec<Stmts>, bool) {
-        let mut had_err = false;
+    /// The statements that parsed fine, and every syntax error
+    pub fn parse(&mut self) -> (Vec<Stmts>, Vec<ParserError>) {
         let mut stmts = Vec::new();
         while self.peeks.peek().is_some() {
-            match self.declaration() {
-                Ok(v) => stmts.push(v),
-                Err(e) => {
-                    had_err = true;
-                    tracing::error!("{e}");
-                },
+            if let Some(stmt) = self.declaration() {
+                stmts.push(stmt);
             }
         }
 
-        (stmts, had_err)
+        (stmts, std::mem::take(&mut self.errors))
     }
 
-    fn declaration(&mut self) -> Result<Stmts> {
-        match self.peeks.peek() {
+    /// Record the error and skip to the next statement when fail
+    fn declaration(&mut self) -> Option<Stmts> {
+        // `fun (` starts a lambda expression statement
+        let named_fun = matches!(self.peeks.peek_nth(1), Some(Token::Identifier { .. }));
+        let res = match self.peeks.peek() {
             Some(Token::Class { .. }) => self.class_declaration(),
-            Some(Token::Fun { .. }) => self.function(FunctionKind::Function),
-            Some(Token::Var { .. }) => self.var_declaration(),
-            _ => match self.statement() {
-                stmt @ Ok(_) => stmt,
-                Err(e) => {
-                    self.synchronize();
-                    Err(ParserError::DeclarationStmt(e.to_string()))
-                },
+            Some(Token::Trait { .. }) => self.trait_declaration(),
+            Some(Token::Import { .. }) => self.import_declaration(),
+            Some(Token::Fun { .. }) if named_fun => self.function(FunctionKind::Function),
+            Some(Token::Var { .. } | Token::Const { .. }) => self.var_declaration(),
+            _ => self.statement(),
+        };
+        match res {
+            Ok(stmt) => Some(stmt),
+            Err(e) => {
+                self.errors.push(e);
+                self.synchronize();
+                None
             },
         }
     }
 
+    /// `import "path.lox" as name;`
+    fn import_declaration(&mut self) -> Result<Stmts> {
+        let keyword = unsafe { self.advance().unwrap_unchecked() };
+        assert!(matches!(keyword, Token::Import { .. }));
+        let start = keyword.inner().offset();
+
+        let path = match self.advance() {
+            Some(tk @ Token::String { .. }) => tk,
+            Some(other) => return Err(ParserError::ImportPath(other)),
+            None => return Err(self.eof("Expect a module path".to_owned())),
+        };
+        match self.advance() {
+            Some(Token::As { .. }) => {},
+            Some(other) => return Err(ParserError::ImportAs(other)),
+            None => return Err(self.eof("Expect `as` after the module path".to_owned())),
+        }
+        let name = match self.advance() {
+            Some(tk @ Token::Identifier { .. }) => tk,
+            Some(other) => return Err(ParserError::ImportAs(other)),
+            None => return Err(self.eof("Expect a module name".to_owned())),
+        };
+        match self.advance() {
+            Some(Token::Semicolon { .. }) => {},
+            Some(v) => return Err(ParserError::Semicolon(v)),
+            None => return Err(self.eof("Expect `;` at end".to_owned())),
+        }
+
+        Ok(Stmts::Import(Import::new(keyword, path, name)).with_span(self.span_from(start)))
+    }
+
+    /// `var name = expr;` or `const name = expr;`
     fn var_declaration(&mut self) -> Result<Stmts> {
-        let var = unsafe { self.peeks.next().unwrap_unchecked() };
-        assert!(matches!(var, Token::Var { .. }));
+        let var = unsafe { self.advance().unwrap_unchecked() };
+        assert!(matches!(var, Token::Var { .. } | Token::Const { .. }));
+        let is_const = matches!(var, Token::Const { .. });
+        let start = var.inner().offset();
+        if matches!(
+            self.peeks.peek(),
+            Some(Token::LeftBracket { .. } | Token::LeftBrace { .. })
+        ) {
+            return self.destructure_declaration(var, is_const, start);
+        }
 
-        let Some(ident) = self.peeks.next()
+        let Some(ident) = self.advance()
         else {
-            return Err(ParserError::Eof("Expect a ident after `var`".to_owned()));
+            return Err(self.eof(format!("Expect a ident after `{}`", var.lexeme())));
         };
         if !matches!(ident, Token::Identifier { .. }) {
             return Err(ParserError::VarDeclaration(ident));
@@ -160,30 +303,105 @@ where
 
         let Some(next_token) = self.peeks.peek()
         else {
-            return Err(ParserError::Eof(format!(
-                "Expect `=` or literal value after {ident}"
-            )));
+            return Err(self.eof(format!("Expect `=` or literal value after {ident}")));
         };
         let init_val = match next_token {
             Token::Equal { .. } => {
-                self.peeks.next();
+                self.advance();
                 self.expression()?
             },
-            _ => Exprs::Literal(Literal::default()),
+            _ if is_const => return Err(ParserError::ConstInit(ident)),
+            _ => Exprs::Literal(Literal::default()).with_span(Span::from(&ident)),
         };
-        match self.peeks.next() {
+        match self.advance() {
+            Some(Token::Semicolon { .. }) => {},
+            Some(v) => return Err(ParserError::Semicolon(v)),
+            None => return Err(self.eof("Expect `;` at end".to_owned())),
+        }
+
+        let var = Var::new(ident, Some(init_val)).with_const(is_const);
+        Ok(Stmts::Var(var).with_span(self.span_from(start)))
+    }
+
+    /// `var [a, ...rest] = value;` or `var {x, y} = value;`, `var` is consumed
+    fn destructure_declaration(
+        &mut self,
+        var: Token,
+        is_const: bool,
+        start: usize,
+    ) -> Result<Stmts> {
+        let pattern = self.unpack_pattern()?;
+        match self.advance() {
+            Some(Token::Equal { .. }) => {},
+            Some(v) => return Err(ParserError::DestructureInit(v)),
+            None => return Err(self.eof("Expect `=` and a value to destructure".to_owned())),
+        }
+        let value = self.expression()?;
+        match self.advance() {
             Some(Token::Semicolon { .. }) => {},
             Some(v) => return Err(ParserError::Semicolon(v)),
-            None => return Err(ParserError::Eof("Expect `;` at end".to_owned())),
+            None => return Err(self.eof("Expect `;` at end".to_owned())),
         }
 
-        Ok(Stmts::Var(Var::new(ident, Some(init_val))))
+        let destructure = Destructure::new(var, pattern, value).with_const(is_const);
+        Ok(Stmts::Destructure(destructure).with_span(self.span_from(start)))
+    }
+
+    /// `[a, b, ...rest]` or `{x, y}`
+    fn unpack_pattern(&mut self) -> Result<Unpack> {
+        let open = unsafe { self.advance().unwrap_unchecked() };
+        let is_list = matches!(open, Token::LeftBracket { .. });
+        let mut names = Vec::new();
+        let mut rest = None;
+        loop {
+            // `...rest` ends a list pattern
+            if is_list && matches!(self.peeks.peek(), Some(Token::DotDotDot { .. })) {
+                self.advance();
+                rest = Some(self.unpack_name()?);
+                match self.advance() {
+                    Some(Token::RightBracket { .. }) => break,
+                    Some(v) => return Err(ParserError::Destructure(v)),
+                    None => return Err(self.eof("Expect `]` after the rest name".to_owned())),
+                }
+            }
+            names.push(self.unpack_name()?);
+            match self.advance() {
+                Some(Token::Comma { .. }) => {},
+                Some(Token::RightBracket { .. }) if is_list => break,
+                Some(Token::RightBrace { .. }) if !is_list => break,
+                Some(v) => return Err(ParserError::Destructure(v)),
+                None => return Err(self.eof("Expect the end of the pattern".to_owned())),
+            }
+        }
+
+        if is_list {
+            Ok(Unpack::List {
+                bracket: open,
+                names,
+                rest,
+            })
+        }
+        else {
+            Ok(Unpack::Fields { brace: open, names })
+        }
+    }
+
+    fn unpack_name(&mut self) -> Result<Token> {
+        match self.advance() {
+            Some(name @ Token::Identifier { .. }) => Ok(name),
+            Some(v) => Err(ParserError::Destructure(v)),
+            None => Err(self.eof("Expect a name to bind".to_owned())),
+        }
     }
 
     fn statement(&mut self) -> Result<Stmts> {
+        // `{x, y} = point;` is an assignment, not a block
+        if self.at_unpack_assign() {
+            return self.expression_stmt();
+        }
         let Some(next) = self.peeks.peek()
         else {
-            return Err(ParserError::Eof("Expect a statement.".to_owned()));
+            return Err(self.eof("Expect a statement.".to_owned()));
         };
 
         match next {
@@ -208,54 +426,71 @@ where
                 Ok(stmt)
             },
             Token::LeftBrace { .. } => {
+                let start = next.inner().offset();
                 let blk_stmt = self.block()?;
-                let stmt = Stmts::Block(Block::new(blk_stmt));
+                let stmt = Stmts::Block(Block::new(blk_stmt)).with_span(self.span_from(start));
                 Ok(stmt)
             },
             Token::Break { .. } => {
                 let stmt = self.break_statement()?;
                 Ok(stmt)
             },
+            Token::Continue { .. } => {
+                let stmt = self.continue_statement()?;
+                Ok(stmt)
+            },
+            Token::Match { .. } => {
+                let stmt = self.match_statement()?;
+                Ok(stmt)
+            },
+            Token::Throw { .. } => self.throw_statement(),
+            Token::Try { .. } => self.try_statement(),
+            Token::Yield { .. } => self.yield_statement(),
             _ => self.expression_stmt(),
         }
     }
 
     fn break_statement(&mut self) -> Result<Stmts> {
-        let break_ = unsafe { self.peeks.next().unwrap_unchecked() };
+        let break_ = unsafe { self.advance().unwrap_unchecked() };
         if self.loop_depth == 0 {
             return Err(ParserError::NotInLoop(break_));
         }
+        let start = break_.inner().offset();
         self.consume_semicolon_paren()?;
-        Ok(Stmts::Break(Break::new(break_)))
+        Ok(Stmts::Break(Break::new(break_)).with_span(self.span_from(start)))
+    }
+
+    fn continue_statement(&mut self) -> Result<Stmts> {
+        let continue_ = unsafe { self.advance().unwrap_unchecked() };
+        if self.loop_depth == 0 {
+            return Err(ParserError::NotInLoop(continue_));
+        }
+        let start = continue_.inner().offset();
+        self.consume_semicolon_paren()?;
+        Ok(Stmts::Continue(Continue::new(continue_)).with_span(self.span_from(start)))
     }
 
     fn while_statement(&mut self) -> Result<Stmts> {
-        let while_ = unsafe { self.peeks.next().unwrap_unchecked() };
+        let while_ = unsafe { self.advance().unwrap_unchecked() };
         assert!(matches!(while_, Token::While { .. }));
+        let start = while_.inner().offset();
 
         self.consume_left_paren()?;
 
         let cond = self.expression()?;
 
         self.consume_rignt_paren()?;
-        let res: Result<Stmts> = try {
-            self.loop_depth += 1;
-            let body = self.statement()?;
+        self.loop_depth += 1;
+        let body = self.statement();
+        self.loop_depth -= 1;
 
-            Stmts::While(While::new(cond, body.into()))
-        };
-        match res {
-            r @ Ok(_) => r,
-            r @ Err(_) => {
-                self.loop_depth -= 1;
-                r
-            },
-        }
+        Ok(Stmts::While(While::new(cond, body?.into())).with_span(self.span_from(start)))
     }
 
     fn if_statement(&mut self) -> Result<Stmts> {
-        let if_ = unsafe { self.peeks.next().unwrap_unchecked() };
+        let if_ = unsafe { self.advance().unwrap_unchecked() };
         assert!(matches!(if_, Token::If { .. }));
+        let start = if_.inner().offset();
 
         self.consume_left_paren()?;
 
@@ -267,50 +502,204 @@ where
 
         let stmts = match self.peeks.peek() {
             Some(Token::Else { .. }) => {
-                self.peeks.next();
+                self.advance();
                 let else_branch = self.statement()?;
                 Stmts::If(If::new(condition, then_branch.into(), else_branch))
             },
             _ => Stmts::If(If::new(condition, Box::new(then_branch), None)),
         };
-        Ok(stmts)
+        Ok(stmts.with_span(self.span_from(start)))
+    }
+
+    fn throw_statement(&mut self) -> Result<Stmts> {
+        let keyword = unsafe { self.advance().unwrap_unchecked() };
+        assert!(matches!(keyword, Token::Throw { .. }));
+        let start = keyword.inner().offset();
+
+        let value = self.expression()?;
+        match self.advance() {
+            Some(Token::Semicolon { .. }) => {},
+            Some(v) => return Err(ParserError::Semicolon(v)),
+            None => return Err(self.eof("Expect `;` at end".to_owned())),
+        }
+
+        Ok(Stmts::Throw(Throw::new(keyword, value)).with_span(self.span_from(start)))
+    }
+
+    fn try_statement(&mut self) -> Result<Stmts> {
+        let keyword = unsafe { self.advance().unwrap_unchecked() };
+        assert!(matches!(keyword, Token::Try { .. }));
+        let start = keyword.inner().offset();
+
+        let body = self.braced_block()?;
+
+        let catch = if matches!(self.peeks.peek(), Some(Token::Catch { .. })) {
+            self.advance();
+            self.consume_left_paren()?;
+            let binding = match self.advance() {
+                Some(tk @ Token::Identifier { .. }) => tk,
+                Some(other) => return Err(ParserError::CatchBinding(other)),
+                None => return Err(self.eof("Expect a name for the caught error".to_owned())),
+            };
+            self.consume_rignt_paren()?;
+            let body = self.braced_block()?;
+            Some(CatchClause { binding, body })
+        }
+        else {
+            None
+        };
+
+        let finally = if matches!(self.peeks.peek(), Some(Token::Finally { .. })) {
+            self.advance();
+            Some(self.braced_block()?)
+        }
+        else {
+            None
+        };
+
+        if catch.is_none() && finally.is_none() {
+            match self.advance() {
+                Some(tk) => return Err(ParserError::TryHandler(tk)),
+                None => return Err(self.eof("Expect `catch` or `finally`".to_owned())),
+            }
+        }
+
+        Ok(Stmts::Try(Try::new(keyword, body, catch, finally)).with_span(self.span_from(start)))
+    }
+
+    /// `{ statements }`, the `{` is checked first
+    fn braced_block(&mut self) -> Result<Vec<Stmts>> {
+        match self.peeks.peek() {
+            Some(Token::LeftBrace { .. }) => self.block(),
+            Some(_) => Err(ParserError::LeftBrace(unsafe {
+                self.advance().unwrap_unchecked()
+            })),
+            None => Err(self.eof("Expect `{`".to_owned())),
+        }
+    }
+
+    fn match_statement(&mut self) -> Result<Stmts> {
+        let keyword = unsafe { self.advance().unwrap_unchecked() };
+        assert!(matches!(keyword, Token::Match { .. }));
+        let start = keyword.inner().offset();
+
+        self.consume_left_paren()?;
+        let value = self.expression()?;
+        self.consume_rignt_paren()?;
+        self.consume_left_brace()?;
+
+        let mut arms = Vec::new();
+        while let Some(tk) = self.peeks.peek()
+            && !matches!(tk, Token::RightBrace { .. })
+        {
+            let pattern = self.pattern()?;
+            match self.advance() {
+                Some(Token::FatArrow { .. }) => {},
+                Some(tk) => return Err(ParserError::FatArrow(tk)),
+                None => return Err(self.eof("Expect `=>` after pattern".to_owned())),
+            }
+            let body = self.statement()?;
+            arms.push(MatchArm { pattern, body });
+            // the comma between arms is optional
+            if matches!(self.peeks.peek(), Some(Token::Comma { .. })) {
+                self.advance();
+            }
+        }
+        self.consume_rignt_brace()?;
+
+        Ok(Stmts::Match(Match::new(keyword, value, arms)).with_span(self.span_from(start)))
+    }
+
+    fn pattern(&mut self) -> Result<Pattern> {
+        let pattern = match self.advance() {
+            Some(token @ Token::Number { double, .. }) => Pattern::Literal {
+                value: LiteralType::Number(double),
+                token,
+            },
+            Some(token @ Token::Minus { .. }) => match self.advance() {
+                Some(Token::Number { double, .. }) => Pattern::Literal {
+                    value: LiteralType::Number(-double),
+                    token,
+                },
+                Some(tk) => return Err(ParserError::Pattern(tk)),
+                None => return Err(self.eof("Expect a number pattern".to_owned())),
+            },
+            Some(Token::String { inner }) => Pattern::Literal {
+                value: LiteralType::String(inner.lexeme_owned()),
+                token: Token::String { inner },
+            },
+            Some(token @ Token::True { .. }) => Pattern::Literal {
+                value: LiteralType::Bool(true),
+                token,
+            },
+            Some(token @ Token::False { .. }) => Pattern::Literal {
+                value: LiteralType::Bool(false),
+                token,
+            },
+            Some(token @ Token::Nil { .. }) => Pattern::Literal {
+                value: LiteralType::Nil,
+                token,
+            },
+            Some(token @ Token::Identifier { .. }) if token.lexeme() == "_" => {
+                Pattern::Wildcard(token)
+            },
+            Some(class @ Token::Identifier { .. }) => match self.advance() {
+                Some(binding @ Token::Identifier { .. }) => Pattern::Class {
+                    class: Variable::new(class.clone()).with_span(Span::from(&class)),
+                    binding,
+                },
+                Some(tk) => return Err(ParserError::Pattern(tk)),
+                None => return Err(self.eof("Expect a name to bind".to_owned())),
+            },
+            Some(tk) => return Err(ParserError::Pattern(tk)),
+            None => return Err(self.eof("Expect a pattern".to_owned())),
+        };
+        Ok(pattern)
     }
 
     fn expression_stmt(&mut self) -> Result<Stmts> {
         let expr = self.expression()?;
-        match self.peeks.next() {
-            Some(Token::Semicolon { .. }) => Ok(Stmts::Expression(Expression::new(expr))),
+        let start = expr.span().start;
+        match self.advance() {
+            Some(Token::Semicolon { .. }) => {
+                Ok(Stmts::Expression(Expression::new(expr)).with_span(self.span_from(start)))
+            },
             Some(tk) => Err(ParserError::Semicolon(tk)),
-            None => Err(ParserError::ErrMessage("Not end of `}`".to_owned())),
+            None => Err(self.eof("Not end of `}`".to_owned())),
         }
     }
 
     fn block(&mut self) -> Result<Vec<Stmts>> {
-        let left_brace = unsafe { self.peeks.next().unwrap_unchecked() };
+        let left_brace = unsafe { self.advance().unwrap_unchecked() };
         assert!(matches!(left_brace, Token::LeftBrace { .. }));
 
         let mut statements = Vec::new();
         while let Some(tk) = self.peeks.peek()
             && !matches!(tk, Token::RightBrace { .. })
         {
-            statements.push(self.declaration()?);
+            if let Some(stmt) = self.declaration() {
+                statements.push(stmt);
+            }
         }
-        match self.peeks.next() {
+        match self.advance() {
             Some(Token::RightBrace { .. }) => Ok(statements),
             Some(v) => Err(ParserError::RightBrace(v)),
-            None => Err(ParserError::ErrMessage("Not end of `}`".to_owned())),
+            None => Err(self.eof("Not end of `}`".to_owned())),
         }
     }
 
     fn print_statement(&mut self) -> Result<Stmts> {
-        let print_ = unsafe { self.peeks.next().unwrap_unchecked() };
+        let print_ = unsafe { self.advance().unwrap_unchecked() };
         assert!(matches!(print_, Token::Print { .. }));
+        let start = print_.inner().offset();
 
         let expr = self.expression()?;
-        match self.peeks.next() {
-            Some(Token::Semicolon { .. }) => Ok(Stmts::Print(Print::new(expr))),
+        match self.advance() {
+            Some(Token::Semicolon { .. }) => {
+                Ok(Stmts::Print(Print::new(expr)).with_span(self.span_from(start)))
+            },
             Some(v) => Err(ParserError::PrintStmt(v)),
-            None => Err(ParserError::ErrMessage("Missing `;` at end".to_owned())),
+            None => Err(self.eof("Missing `;` at end".to_owned())),
         }
     }
 
@@ -319,31 +708,118 @@ where
     }
 
     fn assignment(&mut self) -> Result<Exprs> {
-        let expr = self.or()?;
-        if !matches!(self.peeks.peek(), Some(Token::Equal { .. })) {
+        if self.at_unpack_assign() {
+            return self.unpack_assignment();
+        }
+        let expr = self.conditional()?;
+        if !matches!(
+            self.peeks.peek(),
+            Some(
+                Token::Equal { .. }
+                    | Token::PlusEqual { .. }
+                    | Token::MinusEqual { .. }
+                    | Token::StarEqual { .. }
+                    | Token::SlashEqual { .. }
+                    | Token::PercentEqual { .. }
+            )
+        ) {
             return Ok(expr);
         }
-        let equals = unsafe { self.peeks.next().unwrap_unchecked() };
+        let equals = unsafe { self.advance().unwrap_unchecked() };
+        let operator = (!matches!(equals, Token::Equal { .. })).then(|| equals.clone());
         let value = self.assignment()?;
+        let span = expr.span().to(value.span());
         match expr {
             Exprs::Variable(v) => {
                 let name = v.into_name();
-                Ok(Exprs::Assign(Assign::new(name, value)))
+                let mut assign = Assign::new(name, value).with_span(span);
+                if let Some(operator) = operator {
+                    assign = assign.with_operator(operator);
+                }
+                Ok(Exprs::Assign(assign))
             },
             Exprs::Get(get) => {
-                let set = Exprs::Set(Set::new(*get.object, get.name, value));
-                Ok(set)
+                let mut set = Set::new(*get.object, get.name, value).with_span(span);
+                if let Some(operator) = operator {
+                    set = set.with_operator(operator);
+                }
+                Ok(Exprs::Set(set))
+            },
+            Exprs::Index(index) => {
+                let mut set = SetIndex::new(index, value).with_span(span);
+                if let Some(operator) = operator {
+                    set = set.with_operator(operator);
+                }
+                Ok(Exprs::SetIndex(set))
             },
             _ => Err(ParserError::Assign(equals)),
         }
     }
+
+    /// `[a, ...rest] = value` or `{x, y} = value`
+    fn unpack_assignment(&mut self) -> Result<Exprs> {
+        let start = self.peek_start();
+        let pattern = self.unpack_pattern()?;
+        let equals = unsafe { self.advance().unwrap_unchecked() };
+        assert!(matches!(equals, Token::Equal { .. }));
+        let value = self.assignment()?;
+        Ok(Exprs::AssignUnpack(AssignUnpack::new(pattern, value)).with_span(self.span_from(start)))
+    }
+
+    /// A pattern followed by `=` is ahead, only a plain `=` can unpack
+    fn at_unpack_assign(&mut self) -> bool {
+        let is_list = match self.peeks.peek() {
+            Some(Token::LeftBracket { .. }) => true,
+            Some(Token::LeftBrace { .. }) => false,
+            _ => return false,
+        };
+        let mut n = 1;
+        loop {
+            if is_list && matches!(self.peeks.peek_nth(n), Some(Token::DotDotDot { .. })) {
+                n += 1;
+            }
+            if !matches!(self.peeks.peek_nth(n), Some(Token::Identifier { .. })) {
+                return false;
+            }
+            n += 1;
+            match self.peeks.peek_nth(n) {
+                Some(Token::Comma { .. }) => n += 1,
+                Some(Token::RightBracket { .. }) if is_list => break,
+                Some(Token::RightBrace { .. }) if !is_list => break,
+                _ => return false,
+            }
+        }
+        matches!(self.peeks.peek_nth(n + 1), Some(Token::Equal { .. }))
+    }
+
+    /// right associative: `a ? b : c ? d : e` is `a ? b : (c ? d : e)`
+    fn conditional(&mut self) -> Result<Exprs> {
+        let condition = self.or()?;
+        if !matches!(self.peeks.peek(), Some(Token::Question { .. })) {
+            return Ok(condition);
+        }
+        self.advance();
+        let then_branch = self.expression()?;
+        match self.advance() {
+            Some(Token::Colon { .. }) => {},
+            Some(tk) => return Err(ParserError::Colon(tk)),
+            None => return Err(self.eof("Expect `:` in conditional expression".to_owned())),
+        }
+        let else_branch = self.conditional()?;
+        let span = condition.span().to(else_branch.span());
+        Ok(Exprs::Conditional(
+            Conditional::new(condition, then_branch, else_branch).with_span(span),
+        ))
+    }
+
     fn or(&mut self) -> Result<Exprs> {
         let mut expr = self.and()?;
 
         while let Some(Token::Or { .. }) = self.peeks.peek() {
-            let operator = unsafe { self.peeks.next().unwrap_unchecked() };
+            let operator = unsafe { self.advance().unwrap_unchecked() };
             let right = self.and()?;
-            expr = Exprs::Logical(Logical::new(expr, operator, right));
+            let span = expr.span().to(right.span());
+            expr = Exprs::Logical(Logical::new(expr, operator, right).with_span(span));
         }
 
         Ok(expr)
@@ -352,9 +828,10 @@ where
         let mut expr = self.equality()?;
 
         while let Some(Token::And { .. }) = self.peeks.peek() {
-            let operator = unsafe { self.peeks.next().unwrap_unchecked() };
+            let operator = unsafe { self.advance().unwrap_unchecked() };
             let right = self.and()?;
-            expr = Exprs::Logical(Logical::new(expr, operator, right));
+            let span = expr.span().to(right.span());
+            expr = Exprs::Logical(Logical::new(expr, operator, right).with_span(span));
         }
 
         Ok(expr)
@@ -364,9 +841,10 @@ where
         let mut expr = self.comparison()?;
 
         while let Some(Token::BangEqual { .. } | Token::EqualEqual { .. }) = self.peeks.peek() {
-            let op = unsafe { self.peeks.next().unwrap_unchecked() };
+            let op = unsafe { self.advance().unwrap_unchecked() };
             let right = self.comparison()?;
-            expr = Exprs::Binary(Binary::new(expr, op, right));
+            let span = expr.span().to(right.span());
+            expr = Exprs::Binary(Binary::new(expr, op, right).with_span(span));
         }
 
         Ok(expr)
@@ -382,9 +860,10 @@ where
             | Token::LessEqual { .. },
         ) = self.peeks.peek()
         {
-            let op = unsafe { self.peeks.next().unwrap_unchecked() };
+            let op = unsafe { self.advance().unwrap_unchecked() };
             let right = self.term()?;
-            expr = Exprs::Binary(Binary::new(expr, op, right));
+            let span = expr.span().to(right.span());
+            expr = Exprs::Binary(Binary::new(expr, op, right).with_span(span));
         }
 
         Ok(expr)
@@ -394,9 +873,10 @@ where
         let mut expr = self.factor()?;
 
         while let Some(Token::Minus { .. } | Token::Plus { .. }) = self.peeks.peek() {
-            let operator = unsafe { self.peeks.next().unwrap_unchecked() };
+            let operator = unsafe { self.advance().unwrap_unchecked() };
             let right = self.factor()?;
-            expr = Exprs::Binary(Binary::new(expr, operator, right));
+            let span = expr.span().to(right.span());
+            expr = Exprs::Binary(Binary::new(expr, operator, right).with_span(span));
         }
 
         Ok(expr)
@@ -405,10 +885,13 @@ where
     fn factor(&mut self) -> Result<Exprs> {
         let mut expr = self.unary()?;
 
-        while let Some(Token::Slash { .. } | Token::Star { .. }) = self.peeks.peek() {
-            let operator = unsafe { self.peeks.next().unwrap_unchecked() };
+        while let Some(Token::Slash { .. } | Token::Star { .. } | Token::Percent { .. }) =
+            self.peeks.peek()
+        {
+            let operator = unsafe { self.advance().unwrap_unchecked() };
             let right = self.unary()?;
-            expr = Exprs::Binary(Binary::new(expr, operator, right));
+            let span = expr.span().to(right.span());
+            expr = Exprs::Binary(Binary::new(expr, operator, right).with_span(span));
         }
 
         Ok(expr)
@@ -418,12 +901,87 @@ where
         if let Some(pk) = self.peeks.peek()
             && matches!(pk, Token::Bang { .. } | Token::Minus { .. })
         {
-            let operator = unsafe { self.peeks.next().unwrap_unchecked() };
+            let operator = unsafe { self.advance().unwrap_unchecked() };
             let right = self.unary()?;
-            return Ok(Exprs::Unary(Unary::new(operator, right)));
+            let span = Span::from(&operator).to(right.span());
+            return Ok(Exprs::Unary(Unary::new(operator, right).with_span(span)));
+        }
+        if let Some(Token::PlusPlus { .. } | Token::MinusMinus { .. }) = self.peeks.peek() {
+            let operator = unsafe { self.advance().unwrap_unchecked() };
+            let target = self.unary()?;
+            let span = Span::from(&operator).to(target.span());
+            return Ok(Self::increment(target, operator, false)?.with_span(span));
         }
 
-        self.call()
+        self.power()
+    }
+
+    /// right associative and tighter than unary: `-2 ** 2` is `-(2 ** 2)`, `2 ** -1` works
+    fn power(&mut self) -> Result<Exprs> {
+        let base = self.postfix()?;
+        if !matches!(self.peeks.peek(), Some(Token::StarStar { .. })) {
+            return Ok(base);
+        }
+        let operator = unsafe { self.advance().unwrap_unchecked() };
+        let exponent = self.unary()?;
+        let span = base.span().to(exponent.span());
+        Ok(Exprs::Binary(
+            Binary::new(base, operator, exponent).with_span(span),
+        ))
+    }
+
+    fn postfix(&mut self) -> Result<Exprs> {
+        let expr = self.call()?;
+        if let Some(Token::PlusPlus { .. } | Token::MinusMinus { .. }) = self.peeks.peek() {
+            let operator = unsafe { self.advance().unwrap_unchecked() };
+            let span = expr.span().to(Span::from(&operator));
+            return Ok(Self::increment(expr, operator, true)?.with_span(span));
+        }
+        Ok(expr)
+    }
+
+    /// `++a` is `a += 1`, `a++` is `(a += 1) - 1`, both only work on numbers
+    fn increment(target: Exprs, operator: Token, postfix: bool) -> Result<Exprs> {
+        let inner = operator.inner().clone();
+        let (compound, undo) = match operator {
+            Token::PlusPlus { .. } => (
+                Token::PlusEqual {
+                    inner: inner.clone(),
+                },
+                Token::Minus {
+                    inner: TokenInner::new_cooked(inner.origin().clone(), 2, inner.offset(), "-"),
+                },
+            ),
+            _ => (
+                Token::MinusEqual {
+                    inner: inner.clone(),
+                },
+                Token::Plus {
+                    inner: TokenInner::new_cooked(inner.origin().clone(), 2, inner.offset(), "+"),
+                },
+            ),
+        };
+        let one = || {
+            Exprs::Literal(Literal::new(LiteralType::Number(1.0))).with_span(Span::from(&operator))
+        };
+        let span = target.span().to(Span::from(&operator));
+        let assign = match target {
+            Exprs::Variable(v) => {
+                Exprs::Assign(Assign::new(v.into_name(), one()).with_operator(compound))
+            },
+            Exprs::Get(get) => {
+                Exprs::Set(Set::new(*get.object, get.name, one()).with_operator(compound))
+            },
+            Exprs::Index(index) => {
+                Exprs::SetIndex(SetIndex::new(index, one()).with_operator(compound))
+            },
+            _ => return Err(ParserError::Assign(operator)),
+        }
+        .with_span(span);
+        if !postfix {
+            return Ok(assign);
+        }
+        Ok(Exprs::Binary(Binary::new(assign, undo, one())))
     }
 
     fn call(&mut self) -> Result<Exprs> {
@@ -434,9 +992,17 @@ where
             }
             else if matches!(token, Token::Dot { .. }) {
                 // consume `Dot`
-                self.peeks.next();
+                self.advance();
                 let name = self.consume_identifier()?;
-                expr = Exprs::Get(Get::new(expr, name));
+                let span = expr.span().to(Span::from(&name));
+                expr = Exprs::Get(Get::new(expr, name).with_span(span));
+            }
+            else if matches!(token, Token::LeftBracket { .. }) {
+                let bracket = unsafe { self.advance().unwrap_unchecked() };
+                let index = self.expression()?;
+                self.consume_right_bracket()?;
+                let span = self.span_from(expr.span().start);
+                expr = Exprs::Index(Index::new(expr, bracket, index).with_span(span));
             }
             else {
                 break;
@@ -447,7 +1013,7 @@ where
     }
 
     fn finish_call(&mut self, callee: Exprs) -> Result<Exprs> {
-        let left_paren = unsafe { self.peeks.next().unwrap_unchecked() };
+        let left_paren = unsafe { self.advance().unwrap_unchecked() };
         assert!(matches!(left_paren, Token::LeftParen { .. }));
         let mut args = Vec::new();
         while let Some(tk) = self.peeks.peek()
@@ -463,16 +1029,31 @@ where
                 if !flag {
                     break;
                 }
-                self.peeks.next();
+                self.advance();
             }
         }
         self.consume_rignt_paren()?;
+        let span = self.span_from(callee.span().start);
 
-        Ok(Exprs::Call(Call::new(callee, left_paren, args)))
+        Ok(Exprs::Call(
+            Call::new(callee, left_paren, args).with_span(span),
+        ))
     }
 
     fn primary(&mut self) -> Result<Exprs> {
-        match self.peeks.next() {
+        let start = self.peek_start();
+        // keep the bad token for `synchronize`
+        if let Some(
+            tk @ (Token::Semicolon { .. }
+            | Token::RightParen { .. }
+            | Token::RightBrace { .. }
+            | Token::RightBracket { .. }
+            | Token::Comma { .. }),
+        ) = self.peeks.peek()
+        {
+            return Err(ParserError::Primary(tk.clone()));
+        }
+        let expr = match self.advance() {
             Some(pk) => match pk {
                 Token::False { .. } => Ok(Exprs::Literal(Literal::new(LiteralType::Bool(false)))),
                 Token::True { .. } => Ok(Exprs::Literal(Literal::new(LiteralType::Bool(true)))),
@@ -483,6 +1064,7 @@ where
                 Token::String { inner } => Ok(Exprs::Literal(Literal::new(LiteralType::String(
                     inner.lexeme_owned(),
                 )))),
+                Token::Interpolation { inner } => self.interpolation(inner),
                 sup @ Token::Super { .. } => {
                     let keyword = sup;
                     self.consume_dot()?;
@@ -490,29 +1072,133 @@ where
                     Ok(Exprs::Super(Super::new(keyword, method)))
                 },
                 this @ Token::This { .. } => Ok(Exprs::This(This::new(this))),
+                fun @ Token::Fun { .. } => {
+                    let function = self.function_body(fun)?.with_span(self.span_from(start));
+                    Ok(Exprs::Lambda(Lambda::new(function)))
+                },
                 tk @ Token::Identifier { .. } => Ok(Exprs::Variable(Variable::new(tk))),
                 Token::LeftParen { .. } => {
                     let expr = self.expression()?;
                     self.consume_rignt_paren()?;
                     Ok(Exprs::Grouping(Grouping::new(expr)))
                 },
+                Token::LeftBracket { .. } => self.list(),
+                brace @ Token::LeftBrace { .. } => self.map(brace),
                 other => Err(ParserError::Primary(other)),
             },
-            None => Err(ParserError::Eof("Expect a primary".to_owned())),
+            None => Err(self.eof("Expect a primary".to_owned())),
+        }?;
+        // the start is the first token of this expression
+        Ok(expr.with_span(self.span_from(start)))
+    }
+
+    /// the elements after `[`, a trailing comma is allowed
+    fn list(&mut self) -> Result<Exprs> {
+        let mut elements = Vec::new();
+        while let Some(tk) = self.peeks.peek()
+            && !matches!(tk, Token::RightBracket { .. })
+        {
+            elements.push(self.expression()?);
+            if !matches!(self.peeks.peek(), Some(Token::Comma { .. })) {
+                break;
+            }
+            self.advance();
+        }
+        self.consume_right_bracket()?;
+        Ok(Exprs::List(List::new(elements)))
+    }
+
+    /// the entries after `{`, a trailing comma is allowed
+    fn map(&mut self, brace: Token) -> Result<Exprs> {
+        let mut entries = Vec::new();
+        while let Some(tk) = self.peeks.peek()
+            && !matches!(tk, Token::RightBrace { .. })
+        {
+            let key = self.expression()?;
+            match self.advance() {
+                Some(Token::Colon { .. }) => {},
+                Some(tk) => return Err(ParserError::MapColon(tk)),
+                None => return Err(self.eof("Expect `:` after map key".to_owned())),
+            }
+            let value = self.expression()?;
+            entries.push((key, value));
+            if !matches!(self.peeks.peek(), Some(Token::Comma { .. })) {
+                break;
+            }
+            self.advance();
+        }
+        self.consume_rignt_brace()?;
+        Ok(Exprs::Map(Map::new(brace, entries)))
+    }
+
+    /// `"a ${b} c ${d} e"` desugar to `"a " + str(b) + " c " + str(d) + " e"`
+    fn interpolation(&mut self, head: TokenInner) -> Result<Exprs> {
+        let mut expr = Exprs::Literal(Literal::new(LiteralType::String(head.lexeme_owned())))
+            .with_span(head.span().into());
+        let mut segment = head;
+        loop {
+            let plus = Token::Plus {
+                inner: TokenInner::new_cooked(
+                    segment.origin().clone(),
+                    segment.span().len(),
+                    segment.offset(),
+                    "+",
+                ),
+            };
+            let embedded = self.expression()?;
+            let embedded_span = embedded.span();
+            let to_str = Exprs::Call(Call::new(
+                Exprs::Literal(Literal::new(LiteralType::Callable(Callables::Str(
+                    StrFunction,
+                )))),
+                Token::Interpolation {
+                    inner: segment.clone(),
+                },
+                vec![embedded],
+            ))
+            .with_span(embedded_span);
+            let span = expr.span().to(embedded_span);
+            expr = Exprs::Binary(Binary::new(expr, plus.clone(), to_str)).with_span(span);
+
+            let (next, is_tail) = match self.advance() {
+                Some(Token::Interpolation { inner }) => (inner, false),
+                Some(Token::String { inner }) => (inner, true),
+                Some(other) => return Err(ParserError::Interpolation(other)),
+                None => {
+                    return Err(self.eof("Expect `}` after interpolation".to_owned()));
+                },
+            };
+            if !next.lexeme().is_empty() {
+                let literal =
+                    Exprs::Literal(Literal::new(LiteralType::String(next.lexeme_owned())))
+                        .with_span(next.span().into());
+                let span = expr.span().to(literal.span());
+                expr = Exprs::Binary(Binary::new(expr, plus, literal)).with_span(span);
+            }
+            if is_tail {
+                return Ok(expr);
+            }
+            segment = next;
         }
     }
 
     fn for_statement(&mut self) -> Result<Stmts> {
-        let for_ = unsafe { self.peeks.next().unwrap_unchecked() };
+        let for_ = unsafe { self.advance().unwrap_unchecked() };
         assert!(matches!(for_, Token::For { .. }));
+        let start = for_.inner().offset();
 
         self.consume_left_paren()?;
 
+        if matches!(self.peeks.peek(), Some(Token::Var { .. }))
+            && matches!(self.peeks.peek_nth(1), Some(Token::Identifier { .. }))
+            && matches!(self.peeks.peek_nth(2), Some(Token::In { .. }))
+        {
+            return self.for_in_statement(start);
+        }
+
         let Some(tk) = self.peeks.peek()
         else {
-            return Err(ParserError::Eof(
-                "Expect a varDecl or expr or `;`".to_owned(),
-            ));
+            return Err(self.eof("Expect a varDecl or expr or `;`".to_owned()));
         };
 
         let initializer = match tk {
@@ -523,7 +1209,7 @@ where
 
         let Some(tk) = self.peeks.peek()
         else {
-            return Err(ParserError::Eof("Expect a condition expr".to_owned()));
+            return Err(self.eof("Expect a condition expr".to_owned()));
         };
 
         let condition = match tk {
@@ -534,7 +1220,7 @@ where
 
         let Some(tk) = self.peeks.peek()
         else {
-            return Err(ParserError::Eof("Expect a increment expr".to_owned()));
+            return Err(self.eof("Expect a increment expr".to_owned()));
         };
 
         let increment = match tk {
@@ -543,54 +1229,109 @@ where
         };
         self.consume_rignt_paren()?;
 
-        let res: Result<Stmts> = try {
-            self.loop_depth += 1;
-            let mut body = self.statement()?;
+        self.loop_depth += 1;
+        let body = self.statement();
+        self.loop_depth -= 1;
+        let body = body?;
+
+        // the desugared nodes cover the whole `for`
+        let span = self.span_from(start);
+        let cond = condition.unwrap_or_else(|| {
+            Exprs::Literal(Literal::new(LiteralType::Bool(true))).with_span(Span::from(&for_))
+        });
+        let mut while_ = While::new(cond, body.into());
+        if let Some(increment) = increment {
+            while_ = while_.with_increment(increment);
+        }
+        let mut res = Stmts::While(while_).with_span(span);
 
-            if let Some(increment) = increment {
-                body = Stmts::Block(Block::new(vec![
-                    body,
-                    Stmts::Expression(Expression::new(increment)),
-                ]));
-            }
+        if let Some(initializer) = initializer {
+            res = Stmts::Block(Block::new(vec![initializer, res])).with_span(span);
+        }
 
-            match condition {
-                Some(cond) => {
-                    body = Stmts::While(While::new(cond, body.into()));
-                },
-                None => {
-                    body = Stmts::While(While::new(
-                        Exprs::Literal(Literal::new(LiteralType::Bool(true))),
-                        body.into(),
-                    ));
-                },
-            }
+        Ok(res)
+    }
 
-            if let Some(initializer) = initializer {
-                body = Stmts::Block(Block::new(vec![initializer, body]));
-            }
+    /// `for (var x in xs) body` desugar to
+    /// `{ var <iter> = iter(xs); while (!done(<iter>)) { var x = next(<iter>); body } }`,
+    /// a new `x` for each iteration
+    fn for_in_statement(&mut self, start: usize) -> Result<Stmts> {
+        // consume `var`
+        self.advance();
+        let name = unsafe { self.advance().unwrap_unchecked() };
+        let in_ = unsafe { self.advance().unwrap_unchecked() };
+        let iterable = self.expression()?;
+        self.consume_rignt_paren()?;
 
-            body
+        self.loop_depth += 1;
+        let body = self.statement();
+        self.loop_depth -= 1;
+        let body = body?;
+
+        let span = self.span_from(start);
+        let in_span = Span::from(&in_);
+        let cooked = |lexeme: &str| {
+            let inner = in_.inner();
+            TokenInner::new_cooked(
+                inner.origin().clone(),
+                inner.span().len(),
+                inner.offset(),
+                lexeme,
+            )
         };
-        match res {
-            r @ Ok(_) => r,
-            e @ Err(_) => {
-                self.loop_depth -= 1;
-                e
-            },
-        }
+        let iter_name = Token::Identifier {
+            inner: cooked("<iter>"),
+        };
+        // the two reads are told apart by their span when resolved
+        let iter_var =
+            |span: Span| Exprs::Variable(Variable::new(iter_name.clone())).with_span(span);
+        let step = |fun: IterFunction, arg: Exprs| {
+            let callee = Exprs::Literal(Literal::new(LiteralType::Callable(Callables::Iter(fun))))
+                .with_span(in_span);
+            Exprs::Call(Call::new(callee, in_.clone(), vec![arg])).with_span(in_span)
+        };
+
+        let next = Var::new(
+            name.clone(),
+            Some(step(IterFunction::Next, iter_var(Span::from(&name)))),
+        );
+        let body =
+            Stmts::Block(Block::new(vec![Stmts::Var(next).with_span(span), body])).with_span(span);
+        let bang = Token::Bang { inner: cooked("!") };
+        let cond = Exprs::Unary(Unary::new(
+            bang,
+            step(IterFunction::Done, iter_var(in_span)),
+        ))
+        .with_span(in_span);
+        let while_ = Stmts::While(While::new(cond, body.into())).with_span(span);
+        let iter = Var::new(iter_name, Some(step(IterFunction::Iter, iterable)));
+
+        Ok(
+            Stmts::Block(Block::new(vec![Stmts::Var(iter).with_span(span), while_]))
+                .with_span(span),
+        )
     }
 
     fn function(&mut self, kind: FunctionKind) -> Result<Stmts> {
-        let fun = self.peeks.next();
+        let start = self.peek_start();
+        let fun = self.advance();
         assert!(matches!(fun, Some(Token::Fun { .. })));
-        let name = match self.peeks.next() {
+        let name = match self.advance() {
             Some(tk @ Token::Identifier { .. }) => tk,
             Some(other) => return Err(ParserError::CallDecl { tk: other, kind }),
-            None => return Err(ParserError::Eof(format!("Expect `{kind}` name"))),
+            None => return Err(self.eof(format!("Expect `{kind}` name"))),
         };
+        let function = self.function_body(name)?;
+
+        Ok(Stmts::Function(function).with_span(self.span_from(start)))
+    }
+
+    /// `(params) { body }` of a function named `name`
+    fn function_body(&mut self, name: Token) -> Result<Function> {
         self.consume_left_paren()?;
         let mut parameters = Vec::new();
+        let mut defaults = Vec::new();
+        let mut rest = None;
         if let Some(pk) = self.peeks.peek()
             && !matches!(pk, Token::RightParen { .. })
         {
@@ -599,16 +1340,38 @@ where
                     return Err(ParserError::TooManyArgs(name));
                 }
 
-                let value = match self.peeks.next() {
+                // `...rest`
+                if matches!(self.peeks.peek(), Some(Token::DotDotDot { .. })) {
+                    self.advance();
+                    rest = Some(match self.advance() {
+                        Some(value @ Token::Identifier { .. }) => value,
+                        Some(v) => return Err(ParserError::Parameters(v)),
+                        None => return Err(self.eof("Expect rest parameter".to_owned())),
+                    });
+                    if let Some(comma @ Token::Comma { .. }) = self.peeks.peek() {
+                        return Err(ParserError::RestParam(comma.clone()));
+                    }
+                    break;
+                }
+
+                let value = match self.advance() {
                     Some(value @ Token::Identifier { .. }) => value,
                     Some(v) => return Err(ParserError::Parameters(v)),
-                    None => return Err(ParserError::Eof("Expect parameters".to_owned())),
+                    None => return Err(self.eof("Expect parameters".to_owned())),
                 };
+                // `b = 2`
+                if matches!(self.peeks.peek(), Some(Token::Equal { .. })) {
+                    self.advance();
+                    defaults.push(self.expression()?);
+                }
+                else if !defaults.is_empty() {
+                    return Err(ParserError::DefaultParam(value));
+                }
                 parameters.push(value);
 
                 match self.peeks.peek() {
                     Some(Token::Comma { .. }) => {
-                        self.peeks.next();
+                        self.advance();
                     },
                     _ => break,
                 }
@@ -618,11 +1381,16 @@ where
         // self.consume_left_brace()?;
         let body = self.block()?;
 
-        Ok(Stmts::Function(Function::new(name, parameters, body)))
+        let mut function = Function::new(name, parameters, body).with_defaults(defaults);
+        if let Some(rest) = rest {
+            function = function.with_rest(rest);
+        }
+        Ok(function)
     }
 
     fn return_statement(&mut self) -> Result<Stmts> {
-        let keyword = unsafe { self.peeks.next().unwrap_unchecked() };
+        let keyword = unsafe { self.advance().unwrap_unchecked() };
+        let start = keyword.inner().offset();
         let value = if matches!(self.peeks.peek(), Some(Token::Semicolon { .. })) {
             None
         }
@@ -631,40 +1399,86 @@ where
         };
         self.consume_semicolon_paren()?;
 
-        Ok(Stmts::Return(Return::new(keyword, value)))
+        Ok(Stmts::Return(Return::new(keyword, value)).with_span(self.span_from(start)))
+    }
+
+    fn yield_statement(&mut self) -> Result<Stmts> {
+        let keyword = unsafe { self.advance().unwrap_unchecked() };
+        let start = keyword.inner().offset();
+        let value = if matches!(self.peeks.peek(), Some(Token::Semicolon { .. })) {
+            None
+        }
+        else {
+            Some(self.expression()?)
+        };
+        match self.advance() {
+            Some(Token::Semicolon { .. }) => {},
+            Some(v) => return Err(ParserError::Semicolon(v)),
+            None => return Err(self.eof("Expect `;` at end".to_owned())),
+        }
+
+        Ok(Stmts::Yield(Yield::new(keyword, value)).with_span(self.span_from(start)))
     }
 
     fn class_declaration(&mut self) -> Result<Stmts> {
-        let class = self.peeks.next();
+        let start = self.peek_start();
+        let class = self.advance();
         assert!(matches!(class, Some(Token::Class { .. })));
-        let name = match self.peeks.next() {
+        let name = match self.advance() {
             Some(tk @ Token::Identifier { .. }) => tk,
             Some(other) => return Err(ParserError::Class(other)),
-            None => return Err(ParserError::Eof("Expect class name".to_owned())),
+            None => return Err(self.eof("Expect class name".to_owned())),
         };
 
-        let mut superclass = None;
-
-        if let Some(Token::Less { .. }) = self.peeks.peek() {
-            self.peeks.next();
+        let superclass = if let Some(Token::Less { .. }) = self.peeks.peek() {
+            self.advance();
             // TODO: use `consume_identifier`
-            let tk = match self.peeks.next() {
+            let tk = match self.advance() {
                 Some(tk @ Token::Identifier { .. }) => tk,
                 Some(other) => return Err(ParserError::Superclass(other)),
-                None => return Err(ParserError::Eof("Expect superclass name".to_owned())),
+                None => return Err(self.eof("Expect superclass name".to_owned())),
             };
-            superclass = Some(Variable::new(tk));
+            let span = Span::from(&tk);
+            Some(Variable::new(tk).with_span(span))
+        }
+        else {
+            None
+        };
+
+        let mut traits = Vec::new();
+        if let Some(Token::With { .. }) = self.peeks.peek() {
+            self.advance();
+            traits.push(self.trait_name()?);
+            while let Some(Token::Comma { .. }) = self.peeks.peek() {
+                self.advance();
+                traits.push(self.trait_name()?);
+            }
         }
 
         self.consume_left_brace()?;
 
         let mut methods = Vec::new();
+        let mut static_methods = Vec::new();
+        let mut getters = Vec::new();
 
         while let Some(next) = self.peeks.peek()
             && !matches!(next, Token::RightBrace { .. })
         {
+            // `class fun name() {}`
+            let is_static = matches!(next, Token::Class { .. });
+            if is_static {
+                self.advance();
+            }
+            // `fun name {}`, no parameter list
+            else if matches!(self.peeks.peek_nth(2), Some(Token::LeftBrace { .. })) {
+                getters.push(self.getter()?);
+                continue;
+            }
             let value = self.function(FunctionKind::Method)?;
             match value {
+                Stmts::Function(function) if is_static => {
+                    static_methods.push(function);
+                },
                 Stmts::Function(function) => {
                     methods.push(function);
                 },
@@ -674,7 +1488,73 @@ where
 
         self.consume_rignt_brace()?;
 
-        Ok(Stmts::Class(Class::new(name, superclass, methods)))
+        let class = Class::new(name, superclass, methods)
+            .with_traits(traits)
+            .with_static_methods(static_methods)
+            .with_getters(getters);
+        Ok(Stmts::Class(class).with_span(self.span_from(start)))
+    }
+
+    fn trait_name(&mut self) -> Result<Variable> {
+        match self.advance() {
+            Some(tk @ Token::Identifier { .. }) => {
+                let span = Span::from(&tk);
+                Ok(Variable::new(tk).with_span(span))
+            },
+            Some(other) => Err(ParserError::Trait(other)),
+            None => Err(self.eof("Expect trait name".to_owned())),
+        }
+    }
+
+    fn trait_declaration(&mut self) -> Result<Stmts> {
+        let start = self.peek_start();
+        let keyword = self.advance();
+        assert!(matches!(keyword, Some(Token::Trait { .. })));
+        let name = match self.advance() {
+            Some(tk @ Token::Identifier { .. }) => tk,
+            Some(other) => return Err(ParserError::Trait(other)),
+            None => return Err(self.eof("Expect trait name".to_owned())),
+        };
+
+        self.consume_left_brace()?;
+
+        let mut methods = Vec::new();
+        while let Some(next) = self.peeks.peek()
+            && !matches!(next, Token::RightBrace { .. })
+        {
+            if !matches!(next, Token::Fun { .. }) {
+                return Err(ParserError::CallDecl {
+                    tk: unsafe { self.advance().unwrap_unchecked() },
+                    kind: FunctionKind::Method,
+                });
+            }
+            match self.function(FunctionKind::Method)? {
+                Stmts::Function(function) => methods.push(function),
+                _ => unreachable!("parser function method return not Function variant"),
+            }
+        }
+
+        self.consume_rignt_brace()?;
+
+        Ok(Stmts::Trait(Trait::new(name, methods)).with_span(self.span_from(start)))
+    }
+
+    fn getter(&mut self) -> Result<Function> {
+        let start = self.peek_start();
+        let fun = self.advance();
+        assert!(matches!(fun, Some(Token::Fun { .. })));
+        let name = match self.advance() {
+            Some(tk @ Token::Identifier { .. }) => tk,
+            Some(other) => {
+                return Err(ParserError::CallDecl {
+                    tk: other,
+                    kind: FunctionKind::Method,
+                });
+            },
+            None => return Err(self.eof("Expect `method` name".to_owned())),
+        };
+        let body = self.block()?;
+        Ok(Function::new(name, Vec::new(), body).with_span(self.span_from(start)))
     }
 }
 
@@ -682,77 +1562,125 @@ impl<I> Parser<I>
 where
     I: Iterator<Item = Token>,
 {
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.peeks.next()?;
+        self.prev_end = token.inner().span().end;
+        self.origin = token.inner().origin().clone();
+        Some(token)
+    }
+
+    /// No more token, point at the end of the last one
+    fn eof(&self, msg: String) -> ParserError {
+        ParserError::Eof {
+            msg,
+            at: Token::Eof {
+                inner: TokenInner::new(self.origin.clone(), 0, self.prev_end),
+            },
+        }
+    }
+
+    /// Start byte offset of the next token
+    fn peek_start(&mut self) -> usize {
+        self.peeks
+            .peek()
+            .map_or(self.prev_end, |tk| tk.inner().offset())
+    }
+
+    /// From `start` to the end of the last consumed token
+    const fn span_from(&self, start: usize) -> Span {
+        Span::new(start, self.prev_end)
+    }
+
     /// Consume Identifier and get it's name
     fn consume_identifier(&mut self) -> Result<Token> {
-        match self.peeks.next() {
+        match self.advance() {
             Some(t @ Token::Identifier { .. }) => Ok(t),
             Some(other) => Err(ParserError::LeftParen(other)),
-            None => Err(ParserError::Eof("Expect `;`".to_owned())),
+            None => Err(self.eof("Expect `;`".to_owned())),
         }
     }
     fn consume_dot(&mut self) -> Result<Token> {
-        match self.peeks.next() {
+        match self.advance() {
             Some(t @ Token::Dot { .. }) => Ok(t),
             Some(other) => Err(ParserError::LeftParen(other)),
-            None => Err(ParserError::Eof("Expect `;`".to_owned())),
+            None => Err(self.eof("Expect `;`".to_owned())),
         }
     }
     fn consume_semicolon_paren(&mut self) -> Result<()> {
-        match self.peeks.next() {
+        match self.advance() {
             Some(Token::Semicolon { .. }) => Ok(()),
             Some(other) => Err(ParserError::LeftParen(other)),
-            None => Err(ParserError::Eof("Expect `;`".to_owned())),
+            None => Err(self.eof("Expect `;`".to_owned())),
         }
     }
     /// Expect `Token::LeftParen`, (
     fn consume_left_paren(&mut self) -> Result<()> {
-        match self.peeks.next() {
+        match self.advance() {
             Some(Token::LeftParen { .. }) => Ok(()),
             Some(other) => Err(ParserError::LeftParen(other)),
-            None => Err(ParserError::Eof("Expect `(`".to_owned())),
+            None => Err(self.eof("Expect `(`".to_owned())),
         }
     }
     /// Expect `Token::LeftBrace`, {
     fn consume_left_brace(&mut self) -> Result<()> {
-        match self.peeks.next() {
+        match self.advance() {
             Some(Token::LeftBrace { .. }) => Ok(()),
             Some(other) => Err(ParserError::LeftBrace(other)),
-            None => Err(ParserError::Eof("Expect `{`".to_owned())),
+            None => Err(self.eof("Expect `{`".to_owned())),
         }
     }
 
     /// Expect `Token::RightParen`, )
     fn consume_rignt_paren(&mut self) -> Result<()> {
-        match self.peeks.next() {
+        match self.advance() {
             Some(Token::RightParen { .. }) => Ok(()),
             Some(other) => Err(ParserError::RightParen(other)),
-            None => Err(ParserError::Eof("Expect `)`".to_owned())),
+            None => Err(self.eof("Expect `)`".to_owned())),
+        }
+    }
+    /// Expect `Token::RightBracket`, ]
+    fn consume_right_bracket(&mut self) -> Result<()> {
+        match self.advance() {
+            Some(Token::RightBracket { .. }) => Ok(()),
+            Some(other) => Err(ParserError::RightBracket(other)),
+            None => Err(self.eof("Expect `]`".to_owned())),
         }
     }
     /// Expect `Token::RightBrace`, }
     fn consume_rignt_brace(&mut self) -> Result<()> {
-        match self.peeks.next() {
+        match self.advance() {
             Some(Token::RightBrace { .. }) => Ok(()),
             Some(other) => Err(ParserError::RightBrace(other)),
-            None => Err(ParserError::Eof("Expect `}`".to_owned())),
+            None => Err(self.eof("Expect `}`".to_owned())),
         }
     }
 
     /// when want discard tokens until we're right at the beginning of the next statment
     fn synchronize(&mut self) {
-        self.peeks.next();
-        while let Some(pk) = self.peeks.peek() {
-            match pk {
+        while let Some(tk) = self.advance() {
+            if matches!(tk, Token::Semicolon { .. }) {
+                return;
+            }
+            if let Some(
                 Token::Class { .. }
+                | Token::Trait { .. }
                 | Token::Fun { .. }
                 | Token::Var { .. }
+                | Token::Const { .. }
+                | Token::Import { .. }
                 | Token::For { .. }
                 | Token::If { .. }
                 | Token::While { .. }
                 | Token::Print { .. }
-                | Token::Return { .. } => return,
-                _ => self.peeks.next(),
-            };
+                | Token::Match { .. }
+                | Token::Throw { .. }
+                | Token::Try { .. }
+                | Token::Yield { .. }
+                | Token::Return { .. },
+            ) = self.peeks.peek()
+            {
+                return;
+            }
         }
     }
 }
@@ -2,11 +2,13 @@ use std::rc::Rc;
 
 use pretty_assertions::assert_eq;
 
-use super::Parser;
+use super::{Parser, ParserError};
 use crate::{
+    ast_printer::AstPrinter,
     expr::{Binary, Exprs, Literal, LiteralType, Unary},
     scan::scanner,
-    stmt::{Print, Stmts, Var},
+    span::Span,
+    stmt::{Pattern, Print, Stmts, Var},
     token::{Token, TokenInner},
 };
 
@@ -16,19 +18,29 @@ fn test_equal() {
     let mut scan = scanner::Scanner::new(&source);
     let tks = scan.scan_tokens();
 
-    let right = vec![Stmts::Var(Var::new(
-        Token::Identifier {
-            inner: TokenInner::new(Rc::clone(&source), "a".len(), 4),
-        },
-        Exprs::Binary(Binary::new(
-            Exprs::Literal(Literal::new(LiteralType::Number(1.0))),
-            Token::EqualEqual {
-                inner: TokenInner::new_equal_equal(Rc::clone(&source), 9),
+    let right = vec![
+        Stmts::Var(Var::new(
+            Token::Identifier {
+                inner: TokenInner::new(Rc::clone(&source), "a".len(), 4),
             },
-            Exprs::Literal(Literal::new(LiteralType::Number(1.0))),
+            Exprs::Binary(
+                Binary::new(
+                    Exprs::Literal(
+                        Literal::new(LiteralType::Number(1.0)).with_span(Span::new(8, 9)),
+                    ),
+                    Token::EqualEqual {
+                        inner: TokenInner::new_equal_equal(Rc::clone(&source), 9),
+                    },
+                    Exprs::Literal(
+                        Literal::new(LiteralType::Number(1.0)).with_span(Span::new(11, 12)),
+                    ),
+                )
+                .with_span(Span::new(8, 12)),
+            )
+            .into(),
         ))
-        .into(),
-    ))];
+        .with_span(Span::new(0, 13)),
+    ];
 
     let mut p = Parser::new(tks);
     let (stmts, _) = p.parse();
@@ -37,30 +49,46 @@ fn test_equal() {
 
 #[test]
 fn test_precedence() {
+    let num = |n: f64, start: usize, end: usize| {
+        Exprs::Literal(Literal::new(LiteralType::Number(n)).with_span(Span::new(start, end)))
+    };
+
     let source: Rc<str> = Rc::from("print 6/3+16/2;");
 
     let mut scan = scanner::Scanner::new(&source);
     let tks = scan.scan_tokens();
 
-    let right = vec![Stmts::Print(Print::new(Exprs::Binary(Binary::new(
-        Exprs::Binary(Binary::new(
-            Exprs::Literal(Literal::new(LiteralType::Number(6.0))),
-            Token::Slash {
-                inner: TokenInner::new_slash(Rc::clone(&source), 7),
-            },
-            Exprs::Literal(Literal::new(LiteralType::Number(3.0))),
-        )),
-        Token::Plus {
-            inner: TokenInner::new_plus(Rc::clone(&source), 9),
-        },
-        Exprs::Binary(Binary::new(
-            Exprs::Literal(Literal::new(LiteralType::Number(16.0))),
-            Token::Slash {
-                inner: TokenInner::new_slash(Rc::clone(&source), 12),
-            },
-            Exprs::Literal(Literal::new(LiteralType::Number(2.0))),
-        )),
-    ))))];
+    let right = vec![Stmts::Print(
+        Print::new(Exprs::Binary(
+            Binary::new(
+                Exprs::Binary(
+                    Binary::new(
+                        num(6.0, 6, 7),
+                        Token::Slash {
+                            inner: TokenInner::new_slash(Rc::clone(&source), 7),
+                        },
+                        num(3.0, 8, 9),
+                    )
+                    .with_span(Span::new(6, 9)),
+                ),
+                Token::Plus {
+                    inner: TokenInner::new_plus(Rc::clone(&source), 9),
+                },
+                Exprs::Binary(
+                    Binary::new(
+                        num(16.0, 10, 12),
+                        Token::Slash {
+                            inner: TokenInner::new_slash(Rc::clone(&source), 12),
+                        },
+                        num(2.0, 13, 14),
+                    )
+                    .with_span(Span::new(10, 14)),
+                ),
+            )
+            .with_span(Span::new(6, 14)),
+        ))
+        .with_span(Span::new(0, 15)),
+    )];
     let mut p = Parser::new(tks);
     let (stmts, _) = p.parse();
     assert_eq!(right, stmts);
@@ -70,55 +98,82 @@ fn test_precedence() {
     let mut scan = scanner::Scanner::new(&source);
     let tks = scan.scan_tokens();
 
-    let right = vec![Stmts::Print(Print::new(Exprs::Binary(Binary::new(
-        Exprs::Binary(Binary::new(
-            Exprs::Literal(Literal::new(LiteralType::Number(6.0))),
-            Token::Slash {
-                inner: TokenInner::new_slash(Rc::clone(&source), 7),
-            },
-            Exprs::Literal(Literal::new(LiteralType::Number(3.0))),
-        )),
-        Token::Minus {
-            inner: TokenInner::new_minus(Rc::clone(&source), 9),
-        },
-        Exprs::Binary(Binary::new(
-            Exprs::Literal(Literal::new(LiteralType::Number(16.0))),
-            Token::Star {
-                inner: TokenInner::new_star(Rc::clone(&source), 12),
-            },
-            Exprs::Literal(Literal::new(LiteralType::Number(2.0))),
-        )),
-    ))))];
+    let right = vec![Stmts::Print(
+        Print::new(Exprs::Binary(
+            Binary::new(
+                Exprs::Binary(
+                    Binary::new(
+                        num(6.0, 6, 7),
+                        Token::Slash {
+                            inner: TokenInner::new_slash(Rc::clone(&source), 7),
+                        },
+                        num(3.0, 8, 9),
+                    )
+                    .with_span(Span::new(6, 9)),
+                ),
+                Token::Minus {
+                    inner: TokenInner::new_minus(Rc::clone(&source), 9),
+                },
+                Exprs::Binary(
+                    Binary::new(
+                        num(16.0, 10, 12),
+                        Token::Star {
+                            inner: TokenInner::new_star(Rc::clone(&source), 12),
+                        },
+                        num(2.0, 13, 14),
+                    )
+                    .with_span(Span::new(10, 14)),
+                ),
+            )
+            .with_span(Span::new(6, 14)),
+        ))
+        .with_span(Span::new(0, 15)),
+    )];
 
     let mut p = Parser::new(tks);
     let (stmts, _) = p.parse();
     assert_eq!(right, stmts);
 
     let source: Rc<str> = Rc::from("print 6/3-16*-2;");
-    let right = vec![Stmts::Print(Print::new(Exprs::Binary(Binary::new(
-        Exprs::Binary(Binary::new(
-            Exprs::Literal(Literal::new(LiteralType::Number(6.0))),
-            Token::Slash {
-                inner: TokenInner::new_slash(Rc::clone(&source), 7),
-            },
-            Exprs::Literal(Literal::new(LiteralType::Number(3.0))),
-        )),
-        Token::Minus {
-            inner: TokenInner::new_minus(Rc::clone(&source), 9),
-        },
-        Exprs::Binary(Binary::new(
-            Exprs::Literal(Literal::new(LiteralType::Number(16.0))),
-            Token::Star {
-                inner: TokenInner::new_star(Rc::clone(&source), 12),
-            },
-            Exprs::Unary(Unary::new(
+    let right = vec![Stmts::Print(
+        Print::new(Exprs::Binary(
+            Binary::new(
+                Exprs::Binary(
+                    Binary::new(
+                        num(6.0, 6, 7),
+                        Token::Slash {
+                            inner: TokenInner::new_slash(Rc::clone(&source), 7),
+                        },
+                        num(3.0, 8, 9),
+                    )
+                    .with_span(Span::new(6, 9)),
+                ),
                 Token::Minus {
-                    inner: TokenInner::new_minus(Rc::clone(&source), 13),
+                    inner: TokenInner::new_minus(Rc::clone(&source), 9),
                 },
-                Exprs::Literal(Literal::new(LiteralType::Number(2.0))),
-            )),
-        )),
-    ))))];
+                Exprs::Binary(
+                    Binary::new(
+                        num(16.0, 10, 12),
+                        Token::Star {
+                            inner: TokenInner::new_star(Rc::clone(&source), 12),
+                        },
+                        Exprs::Unary(
+                            Unary::new(
+                                Token::Minus {
+                                    inner: TokenInner::new_minus(Rc::clone(&source), 13),
+                                },
+                                num(2.0, 14, 15),
+                            )
+                            .with_span(Span::new(13, 15)),
+                        ),
+                    )
+                    .with_span(Span::new(10, 15)),
+                ),
+            )
+            .with_span(Span::new(6, 15)),
+        ))
+        .with_span(Span::new(0, 16)),
+    )];
 
     let mut scan = scanner::Scanner::new(&source);
     let tks = scan.scan_tokens();
@@ -136,19 +191,29 @@ fn test_plus_minus_multi_div() {
     let mut scan = scanner::Scanner::new(&source);
     let tks = scan.scan_tokens();
 
-    let right = vec![Stmts::Var(Var::new(
-        Token::Identifier {
-            inner: TokenInner::new(Rc::clone(&source), "a".len(), 4),
-        },
-        Exprs::Binary(Binary::new(
-            Exprs::Literal(Literal::new(LiteralType::Number(1.0))),
-            Token::Plus {
-                inner: TokenInner::new_plus(Rc::clone(&source), 7),
+    let right = vec![
+        Stmts::Var(Var::new(
+            Token::Identifier {
+                inner: TokenInner::new(Rc::clone(&source), "a".len(), 4),
             },
-            Exprs::Literal(Literal::new(LiteralType::Number(1.0))),
+            Exprs::Binary(
+                Binary::new(
+                    Exprs::Literal(
+                        Literal::new(LiteralType::Number(1.0)).with_span(Span::new(6, 7)),
+                    ),
+                    Token::Plus {
+                        inner: TokenInner::new_plus(Rc::clone(&source), 7),
+                    },
+                    Exprs::Literal(
+                        Literal::new(LiteralType::Number(1.0)).with_span(Span::new(8, 9)),
+                    ),
+                )
+                .with_span(Span::new(6, 9)),
+            )
+            .into(),
         ))
-        .into(),
-    ))];
+        .with_span(Span::new(0, 10)),
+    ];
 
     let mut p = Parser::new(tks);
     let (exprs, _) = p.parse();
@@ -160,19 +225,31 @@ fn test_plus_minus_multi_div() {
     let mut scan = scanner::Scanner::new(&source);
     let tks = scan.scan_tokens();
 
-    let right = vec![Stmts::Var(Var::new(
-        Token::Identifier {
-            inner: TokenInner::new(Rc::clone(&source), "a".len(), 4),
-        },
-        Exprs::Binary(Binary::new(
-            Exprs::Literal(Literal::new(LiteralType::String("ab".to_owned()))),
-            Token::Plus {
-                inner: TokenInner::new_plus(Rc::clone(&source), 10),
+    let right = vec![
+        Stmts::Var(Var::new(
+            Token::Identifier {
+                inner: TokenInner::new(Rc::clone(&source), "a".len(), 4),
             },
-            Exprs::Literal(Literal::new(LiteralType::String("cd".to_owned()))),
+            Exprs::Binary(
+                Binary::new(
+                    Exprs::Literal(
+                        Literal::new(LiteralType::String("ab".to_owned()))
+                            .with_span(Span::new(6, 10)),
+                    ),
+                    Token::Plus {
+                        inner: TokenInner::new_plus(Rc::clone(&source), 10),
+                    },
+                    Exprs::Literal(
+                        Literal::new(LiteralType::String("cd".to_owned()))
+                            .with_span(Span::new(11, 15)),
+                    ),
+                )
+                .with_span(Span::new(6, 15)),
+            )
+            .into(),
         ))
-        .into(),
-    ))];
+        .with_span(Span::new(0, 16)),
+    ];
 
     let mut p = Parser::new(tks);
     let (stmts, _) = p.parse();
@@ -184,19 +261,29 @@ fn test_plus_minus_multi_div() {
     let mut scan = scanner::Scanner::new(&source);
     let tks = scan.scan_tokens();
 
-    let right = vec![Stmts::Var(Var::new(
-        Token::Identifier {
-            inner: TokenInner::new(Rc::clone(&source), "a".len(), 4),
-        },
-        Exprs::Binary(Binary::new(
-            Exprs::Literal(Literal::new(LiteralType::Number(1.0))),
-            Token::Minus {
-                inner: TokenInner::new_minus(Rc::clone(&source), 7),
+    let right = vec![
+        Stmts::Var(Var::new(
+            Token::Identifier {
+                inner: TokenInner::new(Rc::clone(&source), "a".len(), 4),
             },
-            Exprs::Literal(Literal::new(LiteralType::Number(1.0))),
+            Exprs::Binary(
+                Binary::new(
+                    Exprs::Literal(
+                        Literal::new(LiteralType::Number(1.0)).with_span(Span::new(6, 7)),
+                    ),
+                    Token::Minus {
+                        inner: TokenInner::new_minus(Rc::clone(&source), 7),
+                    },
+                    Exprs::Literal(
+                        Literal::new(LiteralType::Number(1.0)).with_span(Span::new(8, 9)),
+                    ),
+                )
+                .with_span(Span::new(6, 9)),
+            )
+            .into(),
         ))
-        .into(),
-    ))];
+        .with_span(Span::new(0, 10)),
+    ];
 
     let mut p = Parser::new(tks);
     let (stmts, _) = p.parse();
@@ -208,19 +295,29 @@ fn test_plus_minus_multi_div() {
     let mut scan = scanner::Scanner::new(&source);
     let tks = scan.scan_tokens();
 
-    let right = vec![Stmts::Var(Var::new(
-        Token::Identifier {
-            inner: TokenInner::new(Rc::clone(&source), "a".len(), 4),
-        },
-        Exprs::Binary(Binary::new(
-            Exprs::Literal(Literal::new(LiteralType::Number(1.0))),
-            Token::Star {
-                inner: TokenInner::new_star(Rc::clone(&source), 7),
+    let right = vec![
+        Stmts::Var(Var::new(
+            Token::Identifier {
+                inner: TokenInner::new(Rc::clone(&source), "a".len(), 4),
             },
-            Exprs::Literal(Literal::new(LiteralType::Number(1.0))),
+            Exprs::Binary(
+                Binary::new(
+                    Exprs::Literal(
+                        Literal::new(LiteralType::Number(1.0)).with_span(Span::new(6, 7)),
+                    ),
+                    Token::Star {
+                        inner: TokenInner::new_star(Rc::clone(&source), 7),
+                    },
+                    Exprs::Literal(
+                        Literal::new(LiteralType::Number(1.0)).with_span(Span::new(8, 9)),
+                    ),
+                )
+                .with_span(Span::new(6, 9)),
+            )
+            .into(),
         ))
-        .into(),
-    ))];
+        .with_span(Span::new(0, 10)),
+    ];
 
     let mut p = Parser::new(tks);
     let (stmts, _) = p.parse();
@@ -232,19 +329,29 @@ fn test_plus_minus_multi_div() {
     let mut scan = scanner::Scanner::new(&source);
     let tks = scan.scan_tokens();
 
-    let right = vec![Stmts::Var(Var::new(
-        Token::Identifier {
-            inner: TokenInner::new(Rc::clone(&source), "a".len(), 4),
-        },
-        Exprs::Binary(Binary::new(
-            Exprs::Literal(Literal::new(LiteralType::Number(1.0))),
-            Token::Slash {
-                inner: TokenInner::new_slash(Rc::clone(&source), 7),
+    let right = vec![
+        Stmts::Var(Var::new(
+            Token::Identifier {
+                inner: TokenInner::new(Rc::clone(&source), "a".len(), 4),
             },
-            Exprs::Literal(Literal::new(LiteralType::Number(1.0))),
+            Exprs::Binary(
+                Binary::new(
+                    Exprs::Literal(
+                        Literal::new(LiteralType::Number(1.0)).with_span(Span::new(6, 7)),
+                    ),
+                    Token::Slash {
+                        inner: TokenInner::new_slash(Rc::clone(&source), 7),
+                    },
+                    Exprs::Literal(
+                        Literal::new(LiteralType::Number(1.0)).with_span(Span::new(8, 9)),
+                    ),
+                )
+                .with_span(Span::new(6, 9)),
+            )
+            .into(),
         ))
-        .into(),
-    ))];
+        .with_span(Span::new(0, 10)),
+    ];
 
     let mut p = Parser::new(tks);
     let (stmts, _) = p.parse();
@@ -260,6 +367,380 @@ fn test_paren() {
     }];
 
     let mut p = Parser::new(tks);
-    let (_, had_err) = p.parse();
-    assert!(had_err);
+    let (_, errors) = p.parse();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].span(), Some(Span::new(1, 1)));
+}
+
+#[test]
+fn test_all_errors() {
+    let source = "var 1 = 2;\nprint 1 +;\nvar ok = 1;\nfoo(a, );\nclass { }\nprint ok;";
+    let mut scan = scanner::Scanner::new(source);
+    let mut p = Parser::new(scan.scan_tokens());
+    let (stmts, errors) = p.parse();
+
+    // recover at the next statement
+    assert_eq!(stmts.len(), 2);
+    let located: Vec<_> = errors
+        .iter()
+        .map(|e| e.span().map(|span| &source[span.range()]))
+        .collect();
+    assert_eq!(located, [Some("1"), Some(";"), Some(")"), Some("{"),]);
+}
+
+#[test]
+fn test_spans() {
+    let source = "{ print (1 + 2); }\nfoo(a).b = \"x ${a}\";\nclass A < B { fun m() { return; } }";
+    let mut scan = scanner::Scanner::new(source);
+    let mut p = Parser::new(scan.scan_tokens());
+    let (stmts, errors) = p.parse();
+    assert!(errors.is_empty());
+
+    let code = |span: Span| &source[span.range()];
+    assert_eq!(code(stmts[0].span()), "{ print (1 + 2); }");
+    let Stmts::Block(block) = &stmts[0]
+    else {
+        panic!("not block");
+    };
+    let Stmts::Print(print) = &block.statements()[0]
+    else {
+        panic!("not print");
+    };
+    assert_eq!(code(print.span()), "print (1 + 2);");
+    assert_eq!(code(print.expr().span()), "(1 + 2)");
+
+    let Stmts::Expression(expression) = &stmts[1]
+    else {
+        panic!("not expression");
+    };
+    assert_eq!(code(expression.span()), "foo(a).b = \"x ${a}\";");
+    let Exprs::Set(set) = expression.expr()
+    else {
+        panic!("not set");
+    };
+    assert_eq!(code(set.span()), "foo(a).b = \"x ${a}\"");
+    assert_eq!(code(set.object().span()), "foo(a)");
+    assert_eq!(code(set.value().span()), "\"x ${a}\"");
+
+    let Stmts::Class(class) = &stmts[2]
+    else {
+        panic!("not class");
+    };
+    assert_eq!(code(class.span()), "class A < B { fun m() { return; } }");
+    assert_eq!(class.superclass().map(|s| code(s.span())), Some("B"));
+    assert_eq!(code(class.methods()[0].span()), "fun m() { return; }");
+}
+
+#[test]
+fn test_conditional() {
+    let source = "a ? b : c ? d : e;\nx = a or b ? 1 : 2;";
+    let mut scan = scanner::Scanner::new(source);
+    let mut p = Parser::new(scan.scan_tokens());
+    let (stmts, errors) = p.parse();
+    assert!(errors.is_empty());
+
+    // right associative, binds looser than `or` and tighter than `=`
+    let res = AstPrinter.print(&stmts);
+    assert_eq!(res, "(; (?: a b (?: c d e)))(; (=x(?: (or a b) 1 2)))");
+    assert_eq!(&source[stmts[0].span().range()], "a ? b : c ? d : e;");
+
+    let mut scan = scanner::Scanner::new("a ? b;");
+    let (_, errors) = Parser::new(scan.scan_tokens()).parse();
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(errors[0], super::ParserError::Colon(_)));
+}
+
+#[test]
+fn test_compound_assign() {
+    let source = "a += 1;\nb.c *= a -= 2;\n1 += 2;\n++a;\nb.c--;\n1++;";
+    let mut scan = scanner::Scanner::new(source);
+    let mut p = Parser::new(scan.scan_tokens());
+    let (stmts, errors) = p.parse();
+
+    // right associative like `=`, a postfix increment gives back the old value
+    let res = AstPrinter.print(&stmts);
+    assert_eq!(res, "(; (+=a1))(; (*=bc(-=a2)))(; (++a1))(; (+ (--bc1) 1))");
+    assert_eq!(errors.len(), 2);
+    assert!(matches!(
+        errors[0],
+        super::ParserError::Assign(Token::PlusEqual { .. })
+    ));
+    assert!(matches!(
+        errors[1],
+        super::ParserError::Assign(Token::PlusPlus { .. })
+    ));
+}
+
+#[test]
+fn test_continue() {
+    let source = "for (var i = 0; i < 3; i += 1) continue;\ncontinue;";
+    let mut scan = scanner::Scanner::new(source);
+    let mut p = Parser::new(scan.scan_tokens());
+    let (stmts, errors) = p.parse();
+
+    // the increment is kept apart from the body, so `continue` doesn't skip it
+    let res = AstPrinter.print(&stmts);
+    assert_eq!(res, "(block (vari=0)(while(< i 3)continue(+=i1)))");
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(
+        errors[0],
+        super::ParserError::NotInLoop(Token::Continue { .. })
+    ));
+}
+
+#[test]
+fn test_modulo_power() {
+    let source = "-2 ** 3 ** 2 % 5;\n2 ** -x.y;";
+    let mut scan = scanner::Scanner::new(source);
+    let mut p = Parser::new(scan.scan_tokens());
+    let (stmts, errors) = p.parse();
+    assert!(errors.is_empty());
+
+    // `**` is right associative and binds tighter than unary minus
+    let res = AstPrinter.print(&stmts);
+    assert_eq!(res, "(; (% (- (** 2 (** 3 2))) 5))(; (** 2 (- (.xy))))");
+}
+
+#[test]
+fn test_lambda() {
+    let source = "var f = fun (a, b) { return a; };\nfun () {}();\nfun g() {}";
+    let mut scan = scanner::Scanner::new(source);
+    let mut p = Parser::new(scan.scan_tokens());
+    let (stmts, errors) = p.parse();
+    assert!(errors.is_empty());
+
+    // a `fun` without a name at the start of a statement is an expression
+    let res = AstPrinter.print(&stmts);
+    assert_eq!(res, "(varf=(fun (a b)))(; (call(fun ())))(fun g ()");
+    let Stmts::Var(var) = &stmts[0]
+    else {
+        panic!("not var");
+    };
+    let lambda = var.initializer().unwrap();
+    assert_eq!(&source[lambda.span().range()], "fun (a, b) { return a; }");
+    let Exprs::Lambda(lambda) = lambda
+    else {
+        panic!("not lambda");
+    };
+    assert_eq!(lambda.function().span(), lambda.span());
+}
+
+#[test]
+fn test_list() {
+    let source = "var xs = [1, [2], ];\nxs[0] = xs[1][0];\nxs[0] += 1;\n[1](";
+    let mut scan = scanner::Scanner::new(source);
+    let mut p = Parser::new(scan.scan_tokens());
+    let (stmts, errors) = p.parse();
+    assert_eq!(errors.len(), 1);
+
+    let res = AstPrinter.print(&stmts);
+    assert_eq!(
+        res,
+        "(varxs=(list 1 (list 2)))(; ([]= xs 0 ([] ([] xs 1) 0)))(; ([]+= xs 0 1))"
+    );
+    let Stmts::Expression(set) = &stmts[1]
+    else {
+        panic!("not expression");
+    };
+    assert_eq!(&source[set.expr().span().range()], "xs[0] = xs[1][0]");
+}
+
+#[test]
+fn test_map() {
+    let source = "var m = {\"a\": 1, 2: [3], };\nm[\"a\"] = {};\n{}";
+    let mut scan = scanner::Scanner::new(source);
+    let mut p = Parser::new(scan.scan_tokens());
+    let (stmts, errors) = p.parse();
+    assert!(errors.is_empty());
+
+    // a `{` at the start of a statement is still a block
+    let res = AstPrinter.print(&stmts);
+    assert_eq!(
+        res,
+        "(varm=(map a 1 2 (list 3)))(; ([]= m a (map)))(block )"
+    );
+}
+
+#[test]
+fn test_match() {
+    let source = "match (v) {\n 1 => print 1;\n -2 => {}\n \"x\" => print 3;,\n Point p => print \
+                  p;\n _ => print nil;\n}";
+    let mut scan = scanner::Scanner::new(source);
+    let mut p = Parser::new(scan.scan_tokens());
+    let (stmts, errors) = p.parse();
+    assert!(errors.is_empty());
+
+    let res = AstPrinter.print(&stmts);
+    assert_eq!(
+        res,
+        "(match v (=> 1 (print 1)) (=> -2 (block )) (=> x (print 3)) (=> Point p (print p)) (=> _ \
+         (print nil)))"
+    );
+    let Stmts::Match(match_) = &stmts[0]
+    else {
+        panic!("not match");
+    };
+    let Pattern::Class { class, .. } = &match_.arms()[3].pattern
+    else {
+        panic!("not class pattern");
+    };
+    assert_eq!(&source[class.span().range()], "Point");
+
+    let source = "match (v) { 1 print 1; }";
+    let mut scan = scanner::Scanner::new(source);
+    let mut p = Parser::new(scan.scan_tokens());
+    let (_, errors) = p.parse();
+    assert!(matches!(errors[0], ParserError::FatArrow(_)));
+}
+
+#[test]
+fn test_for_in() {
+    let source = "for (var x in xs) print x;";
+    let mut scan = scanner::Scanner::new(source);
+    let mut p = Parser::new(scan.scan_tokens());
+    let (stmts, errors) = p.parse();
+    assert!(errors.is_empty());
+
+    // a new `x` in the block of each iteration
+    let res = AstPrinter.print(&stmts);
+    assert_eq!(
+        res,
+        "(block (var<iter>=(call<fn iter>(inner)xs))(while(! (call<fn done>(inner)<iter>))(block \
+         (varx=(call<fn next>(inner)<iter>))(print x))))"
+    );
+    assert_eq!(&source[stmts[0].span().range()], source);
+}
+
+#[test]
+fn test_default_rest_params() {
+    let source = "fun f(a, b = a + 1, ...rest) {}\nfun g(...all) {}\nfun h(a = 1, b) {}";
+    let mut scan = scanner::Scanner::new(source);
+    let mut p = Parser::new(scan.scan_tokens());
+    let (stmts, errors) = p.parse();
+    assert!(matches!(errors[..], [ParserError::DefaultParam(_)]));
+
+    let res = AstPrinter.print(&stmts);
+    assert_eq!(res, "(fun f (a b=(+ a 1) ...rest)(fun g (...all)");
+}
+
+#[test]
+fn test_static_getter() {
+    let source = "class A { class fun make() { return A(); } fun size { return 1; } fun get() {} }";
+    let mut scan = scanner::Scanner::new(source);
+    let mut p = Parser::new(scan.scan_tokens());
+    let (stmts, errors) = p.parse();
+    assert!(errors.is_empty());
+
+    let res = AstPrinter.print(&stmts);
+    assert_eq!(
+        res,
+        "(class A (fun get () (static (fun make ()) (getter (fun size ()))"
+    );
+}
+
+#[test]
+fn test_trait() {
+    let source = "trait T { fun f() {} }\nclass A < B with T, U {}\nclass C with {}";
+    let mut scan = scanner::Scanner::new(source);
+    let mut p = Parser::new(scan.scan_tokens());
+    let (stmts, errors) = p.parse();
+    assert!(matches!(errors[..], [ParserError::Trait(_)]));
+
+    let res = AstPrinter.print(&stmts);
+    assert_eq!(res, "(trait T (fun f ())(class A < B with T U)");
+}
+
+#[test]
+fn test_const() {
+    let source = "const a = 1;\nconst b;";
+    let mut scan = scanner::Scanner::new(source);
+    let mut p = Parser::new(scan.scan_tokens());
+    let (stmts, errors) = p.parse();
+    assert!(matches!(errors[..], [ParserError::ConstInit(_)]));
+
+    let res = AstPrinter.print(&stmts);
+    assert_eq!(res, "(consta=1)");
+}
+
+#[test]
+fn test_import() {
+    let source = "import \"util/math.lox\" as m;\nimport m;\nimport \"a.lox\" m;";
+    let mut scan = scanner::Scanner::new(source);
+    let mut p = Parser::new(scan.scan_tokens());
+    let (stmts, errors) = p.parse();
+    assert!(matches!(
+        errors[..],
+        [ParserError::ImportPath(_), ParserError::ImportAs(_)]
+    ));
+
+    let res = AstPrinter.print(&stmts);
+    assert_eq!(res, "(import util/math.lox as m)");
+}
+
+#[test]
+fn test_try_throw() {
+    let source =
+        "try {} catch (1) {}\ntry {} print 1;\ntry { throw 1; } catch (e) { print e; } finally {}";
+    let mut scan = scanner::Scanner::new(source);
+    let mut p = Parser::new(scan.scan_tokens());
+    let (stmts, errors) = p.parse();
+    assert!(matches!(
+        errors[..],
+        [ParserError::CatchBinding(_), ParserError::TryHandler(_)]
+    ));
+
+    let res = AstPrinter.print(&stmts);
+    assert_eq!(
+        res,
+        "(try (block (throw 1)) (catch e (print e)) (finally ))"
+    );
+}
+
+#[test]
+fn test_yield() {
+    let source = "yield 1;\nyield;\nyield 1 2;";
+    let mut scan = scanner::Scanner::new(source);
+    let mut p = Parser::new(scan.scan_tokens());
+    let (stmts, errors) = p.parse();
+    assert!(matches!(errors[..], [ParserError::Semicolon(_)]));
+
+    let res = AstPrinter.print(&stmts);
+    assert_eq!(res, "(yield 1)(yield)");
+    assert_eq!(&source[stmts[0].span().range()], "yield 1;");
+}
+
+#[test]
+fn test_destructure() {
+    let source = "var [a, ...rest] = xs;\nconst {x, y} = p;\nvar [a, ...b, c] = xs;\nvar {x};";
+    let mut scan = scanner::Scanner::new(source);
+    let mut p = Parser::new(scan.scan_tokens());
+    let (stmts, errors) = p.parse();
+    assert!(matches!(
+        errors[..],
+        [ParserError::Destructure(_), ParserError::DestructureInit(_)]
+    ));
+
+    let res = AstPrinter.print(&stmts);
+    assert_eq!(res, "(var [a, ...rest] = xs)(const {x, y} = p)");
+}
+
+#[test]
+fn test_destructure_assign() {
+    let source = "[a, ...rest] = xs;\n{x, y} = p;\nprint [a, b] = [b, a];\n[a, b];\n{ x; }";
+    let mut scan = scanner::Scanner::new(source);
+    let mut p = Parser::new(scan.scan_tokens());
+    let (stmts, errors) = p.parse();
+    assert!(errors.is_empty());
+
+    let res = AstPrinter.print(&stmts);
+    assert_eq!(
+        res,
+        "(; (= [a, ...rest] xs))(; (= {x, y} p))(print (= [a, b] (list b a)))(; (list a b))(block \
+         (; x))"
+    );
+    let Stmts::Expression(expr) = &stmts[0]
+    else {
+        unreachable!()
+    };
+    assert_eq!(expr.expr().span(), Span::new(0, 17));
 }
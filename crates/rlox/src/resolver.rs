@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::{
     expr::*,
@@ -11,12 +11,34 @@ use crate::{
 #[derive(PartialEq, Eq)]
 pub struct Resolver<'i> {
     pub interpreter: &'i mut Interpreter,
-    pub scopes: Vec<HashMap<String, bool>>,
+    pub scopes: Vec<HashMap<String, Binding>>,
     current_fun: FunctionType,
     current_class: ClassType,
+    /// loops around the current statement, inside the current function
+    loop_depth: usize,
+    /// global `const`s of the statements being resolved, known before their declarations
+    global_consts: HashSet<String>,
     had_err: bool,
 }
 
+/// What the resolver knows about a name in a scope
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+pub struct Binding {
+    /// its initializer is resolved
+    pub defined: bool,
+    /// declared with `const`
+    pub is_const: bool,
+}
+
+impl Binding {
+    const DEFINED: Self = Self {
+        defined: true,
+        is_const: false,
+    };
+}
+
 #[derive(Clone, Copy)]
 #[derive(Debug)]
 #[derive(Default)]
@@ -47,6 +69,8 @@ impl<'i> Resolver<'i> {
             scopes: Vec::new(),
             current_fun: FunctionType::None,
             current_class: ClassType::Class,
+            loop_depth: 0,
+            global_consts: HashSet::new(),
             had_err: false,
         }
     }
@@ -61,6 +85,22 @@ impl<'i> Resolver<'i> {
     }
 
     pub fn resolve(&mut self, statements: &[Stmts]) -> bool {
+        if self.scopes.is_empty() {
+            // a function resolved before a global `const` can't assign it either
+            for stmt in statements {
+                match stmt {
+                    Stmts::Var(var) if var.is_const() => {
+                        self.global_consts.insert(var.var_name().to_owned());
+                    },
+                    Stmts::Destructure(destructure) if destructure.is_const() => {
+                        let names = destructure.pattern().names();
+                        self.global_consts
+                            .extend(names.map(|name| name.lexeme().to_owned()));
+                    },
+                    _ => {},
+                }
+            }
+        }
         for stmt in statements {
             if let Err(e) = self.resolve_stmt(stmt) {
                 tracing::error!("{e}");
@@ -83,28 +123,62 @@ impl<'i> Resolver<'i> {
     }
 
     fn declare(&mut self, name: &crate::token::Token) -> Result<()> {
+        self.declare_binding(name, false)
+    }
+
+    fn declare_binding(&mut self, name: &crate::token::Token, is_const: bool) -> Result<()> {
         let Some(last) = self.scopes.last_mut()
         else {
-            // Without local scope it will look global
+            // Without local scope it will look global, a global const can't be redeclared
+            if self.interpreter.is_const_global(name.lexeme()) {
+                return Err(ParserError::DoubleVar(name.clone()));
+            }
+            if is_const {
+                self.interpreter.define_const_global(name.lexeme());
+            }
             return Ok(());
         };
         if last.contains_key(name.lexeme()) {
             return Err(ParserError::DoubleVar(name.clone()));
         }
-        last.insert(name.lexeme().to_owned(), false);
+        last.insert(
+            name.lexeme().to_owned(),
+            Binding {
+                defined: false,
+                is_const,
+            },
+        );
         Ok(())
     }
 
     fn define(&mut self, name: &crate::token::Token) {
-        if let Some(last) = self.scopes.last_mut() {
-            last.insert(name.lexeme().to_owned(), true);
+        if let Some(last) = self.scopes.last_mut()
+            && let Some(binding) = last.get_mut(name.lexeme())
+        {
+            binding.defined = true;
         }
     }
 
+    /// The innermost binding of `name` is a `const`
+    fn is_const(&self, name: &crate::token::Token) -> bool {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name.lexeme()))
+            .map_or_else(
+                || {
+                    self.interpreter.is_const_global(name.lexeme())
+                        || self.global_consts.contains(name.lexeme())
+                },
+                |binding| binding.is_const,
+            )
+    }
+
     fn resolve_local(&mut self, expr: &Exprs, name: &crate::token::Token) {
-        for (i, ele) in self.scopes.iter().enumerate() {
+        // the innermost scope wins, so shadowing works
+        for (depth, ele) in self.scopes.iter().rev().enumerate() {
             if ele.contains_key(name.lexeme()) {
-                self.interpreter.resolve(expr, self.scopes.len() - 1 - i);
+                self.interpreter.resolve(expr, depth);
                 return;
             }
         }
@@ -113,14 +187,25 @@ impl<'i> Resolver<'i> {
     fn resolve_function(&mut self, stmt: &Function, ft: FunctionType) -> Result<()> {
         let enclosing_fun = self.current_fun;
         self.current_fun = ft;
+        // `break` and `continue` can't jump out of a function
+        let enclosing_loop_depth = std::mem::take(&mut self.loop_depth);
         self.begin_scope();
 
-        for ele in &stmt.params {
+        for (idx, ele) in stmt.params.iter().enumerate() {
+            // it sees the params before it
+            if let Some(default) = stmt.default_of(idx) {
+                self.resolve_expr(default)?;
+            }
             self.declare(ele)?;
             self.define(ele);
         }
+        if let Some(rest) = &stmt.rest {
+            self.declare(rest)?;
+            self.define(rest);
+        }
         self.resolve(&stmt.body);
         self.end_scope();
+        self.loop_depth = enclosing_loop_depth;
         self.current_fun = enclosing_fun;
         Ok(())
     }
@@ -128,12 +213,26 @@ impl<'i> Resolver<'i> {
 
 impl crate::expr::ExprVisitor<Result<()>> for Resolver<'_> {
     fn visit_assign_expr(&mut self, expr: &Assign) -> Result<()> {
+        if self.is_const(expr.name()) {
+            return Err(ParserError::AssignConst(expr.name().clone()));
+        }
         self.resolve_expr(expr.value())?;
         // PERF: avoid `.clone()` add `self.resolve_local_assign` method
         self.resolve_local(&Exprs::Assign(expr.clone()), expr.name());
         Ok(())
     }
 
+    fn visit_assignunpack_expr(&mut self, expr: &AssignUnpack) -> Result<()> {
+        if let Some(name) = expr.pattern().names().find(|name| self.is_const(name)) {
+            return Err(ParserError::AssignConst(name.clone()));
+        }
+        self.resolve_expr(expr.value())?;
+        for name in expr.pattern().names() {
+            self.resolve_local(&AssignUnpack::target(name), name);
+        }
+        Ok(())
+    }
+
     fn visit_binary_expr(&mut self, expr: &Binary) -> Result<()> {
         self.resolve_expr(expr.left())?;
         self.resolve_expr(expr.right())
@@ -147,6 +246,12 @@ impl crate::expr::ExprVisitor<Result<()>> for Resolver<'_> {
         Ok(())
     }
 
+    fn visit_conditional_expr(&mut self, expr: &Conditional) -> Result<()> {
+        self.resolve_expr(expr.condition())?;
+        self.resolve_expr(expr.then_branch())?;
+        self.resolve_expr(expr.else_branch())
+    }
+
     fn visit_get_expr(&mut self, expr: &Get) -> Result<()> {
         self.resolve_expr(expr.object())
     }
@@ -155,6 +260,30 @@ impl crate::expr::ExprVisitor<Result<()>> for Resolver<'_> {
         self.resolve_expr(expr.expression())
     }
 
+    fn visit_index_expr(&mut self, expr: &Index) -> Result<()> {
+        self.resolve_expr(expr.object())?;
+        self.resolve_expr(expr.index())
+    }
+
+    fn visit_list_expr(&mut self, expr: &List) -> Result<()> {
+        for ele in expr.elements() {
+            self.resolve_expr(ele)?;
+        }
+        Ok(())
+    }
+
+    fn visit_map_expr(&mut self, expr: &Map) -> Result<()> {
+        for (key, value) in expr.entries() {
+            self.resolve_expr(key)?;
+            self.resolve_expr(value)?;
+        }
+        Ok(())
+    }
+
+    fn visit_lambda_expr(&mut self, expr: &Lambda) -> Result<()> {
+        self.resolve_function(expr.function(), FunctionType::Function)
+    }
+
     fn visit_literal_expr(&mut self, _expr: &Literal) -> Result<()> {
         Ok(())
     }
@@ -169,6 +298,12 @@ impl crate::expr::ExprVisitor<Result<()>> for Resolver<'_> {
         self.resolve_expr(expr.object())
     }
 
+    fn visit_setindex_expr(&mut self, expr: &SetIndex) -> Result<()> {
+        self.resolve_expr(expr.value())?;
+        self.resolve_expr(expr.object())?;
+        self.resolve_expr(expr.index())
+    }
+
     fn visit_super_expr(&mut self, expr: &Super) -> Result<()> {
         match self.current_class {
             ClassType::None => return Err(ParserError::NotInClassSuper(expr.keyword().clone())),
@@ -193,7 +328,9 @@ impl crate::expr::ExprVisitor<Result<()>> for Resolver<'_> {
 
     fn visit_variable_expr(&mut self, expr: &Variable) -> Result<()> {
         if let Some(last) = self.scopes.last()
-            && last.get(expr.name_str()) == Some(&false)
+            && last
+                .get(expr.name_str())
+                .is_some_and(|binding| !binding.defined)
         {
             return Err(ParserError::Initialization(expr.name().clone()));
         }
@@ -214,7 +351,7 @@ impl crate::stmt::StmtVisitor<Result<()>> for Resolver<'_> {
     }
 
     fn visit_var_stmt(&mut self, stmt: &Var) -> Result<()> {
-        self.declare(stmt.name())?;
+        self.declare_binding(stmt.name(), stmt.is_const())?;
 
         if let Some(v) = stmt.initializer() {
             self.resolve_expr(v)?;
@@ -225,6 +362,17 @@ impl crate::stmt::StmtVisitor<Result<()>> for Resolver<'_> {
         Ok(())
     }
 
+    fn visit_destructure_stmt(&mut self, stmt: &Destructure) -> Result<()> {
+        for name in stmt.pattern().names() {
+            self.declare_binding(name, stmt.is_const())?;
+        }
+        self.resolve_expr(stmt.value())?;
+        for name in stmt.pattern().names() {
+            self.define(name);
+        }
+        Ok(())
+    }
+
     fn visit_block_stmt(&mut self, stmt: &Block) -> Result<()> {
         self.begin_scope();
         self.resolve(stmt.statements());
@@ -244,10 +392,54 @@ impl crate::stmt::StmtVisitor<Result<()>> for Resolver<'_> {
 
     fn visit_while_stmt(&mut self, stmt: &While) -> Result<()> {
         self.resolve_expr(stmt.condition())?;
-        self.resolve_stmt(stmt.body())
+        if let Some(increment) = stmt.increment() {
+            self.resolve_expr(increment)?;
+        }
+        self.loop_depth += 1;
+        let res = self.resolve_stmt(stmt.body());
+        self.loop_depth -= 1;
+        res
+    }
+
+    fn visit_break_stmt(&mut self, stmt: &Break) -> Result<()> {
+        if self.loop_depth == 0 {
+            return Err(ParserError::NotInLoop(stmt.token().clone()));
+        }
+        Ok(())
     }
 
-    fn visit_break_stmt(&mut self, _stmt: &Break) -> Result<()> {
+    fn visit_continue_stmt(&mut self, stmt: &Continue) -> Result<()> {
+        if self.loop_depth == 0 {
+            return Err(ParserError::NotInLoop(stmt.token().clone()));
+        }
+        Ok(())
+    }
+
+    fn visit_match_stmt(&mut self, stmt: &Match) -> Result<()> {
+        self.resolve_expr(stmt.value())?;
+        let mut wildcard = false;
+        for arm in stmt.arms() {
+            if wildcard {
+                tracing::warn!("Unreachable match arm after `_`: {}", arm.pattern.token());
+            }
+            match &arm.pattern {
+                Pattern::Literal { .. } => self.resolve_stmt(&arm.body)?,
+                Pattern::Wildcard(_) => {
+                    wildcard = true;
+                    self.resolve_stmt(&arm.body)?;
+                },
+                Pattern::Class { class, binding } => {
+                    self.resolve_expr_variable(class)?;
+                    // env for the bound instance
+                    self.begin_scope();
+                    self.declare(binding)?;
+                    self.define(binding);
+                    let res = self.resolve_stmt(&arm.body);
+                    self.end_scope();
+                    res?;
+                },
+            }
+        }
         Ok(())
     }
 
@@ -279,6 +471,10 @@ impl crate::stmt::StmtVisitor<Result<()>> for Resolver<'_> {
         self.declare(stmt.name())?;
         self.define(stmt.name());
 
+        for trait_ in stmt.traits() {
+            self.resolve_expr_variable(trait_)?;
+        }
+
         if let Some(superclass) = stmt.superclass() {
             if stmt.name().lexeme() == superclass.name().lexeme() {
                 return Err(ParserError::RecurseClass(superclass.name().clone()));
@@ -295,7 +491,7 @@ impl crate::stmt::StmtVisitor<Result<()>> for Resolver<'_> {
                 self.scopes
                     .last_mut()
                     .unwrap_unchecked()
-                    .insert("super".to_owned(), true)
+                    .insert("super".to_owned(), Binding::DEFINED)
             };
         }
 
@@ -305,7 +501,7 @@ impl crate::stmt::StmtVisitor<Result<()>> for Resolver<'_> {
             self.scopes
                 .last_mut()
                 .unwrap_unchecked()
-                .insert("this".to_owned(), true)
+                .insert("this".to_owned(), Binding::DEFINED)
         };
 
         for method in stmt.methods() {
@@ -317,9 +513,20 @@ impl crate::stmt::StmtVisitor<Result<()>> for Resolver<'_> {
             };
             self.resolve_function(method, declaration)?;
         }
+        for getter in stmt.getters() {
+            self.resolve_function(getter, FunctionType::Method)?;
+        }
 
         self.end_scope();
 
+        // static methods see `super`'s env but have no `this`
+        let class_type = self.current_class;
+        self.current_class = ClassType::None;
+        for method in stmt.static_methods() {
+            self.resolve_function(method, FunctionType::Function)?;
+        }
+        self.current_class = class_type;
+
         if stmt.superclass().is_some() {
             self.end_scope();
         }
@@ -328,4 +535,83 @@ impl crate::stmt::StmtVisitor<Result<()>> for Resolver<'_> {
 
         Ok(())
     }
+
+    fn visit_trait_stmt(&mut self, stmt: &Trait) -> Result<()> {
+        let enclosing_class = self.current_class;
+        self.current_class = ClassType::Class;
+
+        self.declare(stmt.name())?;
+        self.define(stmt.name());
+
+        self.begin_scope();
+
+        unsafe {
+            self.scopes
+                .last_mut()
+                .unwrap_unchecked()
+                .insert("this".to_owned(), Binding::DEFINED)
+        };
+
+        for method in stmt.methods() {
+            let declaration = if method.name.lexeme().eq("init") {
+                FunctionType::Initializer
+            }
+            else {
+                FunctionType::Method
+            };
+            self.resolve_function(method, declaration)?;
+        }
+
+        self.end_scope();
+
+        self.current_class = enclosing_class;
+
+        Ok(())
+    }
+
+    fn visit_import_stmt(&mut self, stmt: &Import) -> Result<()> {
+        self.declare(stmt.name())?;
+        self.define(stmt.name());
+        Ok(())
+    }
+
+    fn visit_throw_stmt(&mut self, stmt: &Throw) -> Result<()> {
+        self.resolve_expr(stmt.value())
+    }
+
+    fn visit_try_stmt(&mut self, stmt: &Try) -> Result<()> {
+        self.begin_scope();
+        self.resolve(stmt.body());
+        self.end_scope();
+
+        if let Some(catch) = stmt.catch() {
+            // env for the caught value
+            self.begin_scope();
+            self.declare(&catch.binding)?;
+            self.define(&catch.binding);
+            self.resolve(&catch.body);
+            self.end_scope();
+        }
+
+        if let Some(finally) = stmt.finally() {
+            self.begin_scope();
+            self.resolve(finally);
+            self.end_scope();
+        }
+        Ok(())
+    }
+
+    fn visit_yield_stmt(&mut self, stmt: &Yield) -> Result<()> {
+        match self.current_fun {
+            FunctionType::None => return Err(ParserError::YieldNotInFn(stmt.keyword().clone())),
+            FunctionType::Initializer => {
+                return Err(ParserError::YieldInInit(stmt.keyword().clone()));
+            },
+            FunctionType::Function | FunctionType::Method => {},
+        }
+        if let Some(v) = stmt.value() {
+            self.resolve_expr(v)?;
+        }
+        Ok(())
+    }
 }
@@ -1,4 +1,5 @@
 pub mod scanner;
+pub mod trivia;
 
 #[cfg(test)]
 mod tests;
@@ -1,15 +1,95 @@
 #![allow(unfulfilled_lint_expectations, reason = "allow it")]
-use std::{rc::Rc, str::CharIndices};
+use std::{fmt::Display, ops::Range, str::CharIndices};
 
 use itertools::PeekNth;
 
-use crate::token::{Token, TokenInner};
+use super::trivia::{LosslessToken, attach_trivia};
+use crate::token::{Source, Token, TokenInner};
+
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+pub enum ScanErrorKind {
+    UnterminatedString,
+    InvalidEscape,
+    UnexpectedChar,
+    UnterminatedBlockComment,
+    MalformedNumber(NumberError),
+}
+
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+pub enum NumberError {
+    /// `12ab`, `0b102`, `0o8`
+    InvalidDigit,
+    /// `0x`, `1e`, `1e+`
+    MissingDigits,
+    /// `1__0`, `1_`, `0x_f`, `1_.5`
+    MisplacedSeparator,
+}
+
+impl Display for NumberError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidDigit => f.write_str("invalid digit"),
+            Self::MissingDigits => f.write_str("missing digits"),
+            Self::MisplacedSeparator => f.write_str("`_` must be between digits"),
+        }
+    }
+}
+
+impl Display for ScanErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnterminatedString => f.write_str("Unterminated string"),
+            Self::InvalidEscape => f.write_str("Invalid escape sequence"),
+            Self::UnexpectedChar => f.write_str("Unexpected character"),
+            Self::UnterminatedBlockComment => f.write_str("Unterminated block comment"),
+            Self::MalformedNumber(e) => write!(f, "Malformed number, {e}"),
+        }
+    }
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+#[derive(thiserror::Error)]
+#[error("{kind}: {inner}")]
+pub struct ScanError {
+    kind: ScanErrorKind,
+    inner: TokenInner,
+}
+
+impl ScanError {
+    pub const fn new(kind: ScanErrorKind, inner: TokenInner) -> Self {
+        Self { kind, inner }
+    }
+
+    pub const fn kind(&self) -> ScanErrorKind {
+        self.kind
+    }
+
+    /// Byte range of the bad code in the source
+    pub const fn span(&self) -> Range<usize> {
+        self.inner.span()
+    }
+
+    pub const fn inner(&self) -> &TokenInner {
+        &self.inner
+    }
+}
 
 // #[derive(Clone)]
 #[derive(Debug)]
 pub struct Scanner<'s> {
     source_chars: PeekNth<CharIndices<'s>>,
     source: &'s str,
+    /// shared by every token, built once
+    origin: Source,
+    errors: Vec<ScanError>,
+    /// Open `${` of string interpolations: (segment offset, depth of `{` inside the expression)
+    interpolations: Vec<(usize, usize)>,
 }
 
 impl<'s> Scanner<'s> {
@@ -17,9 +97,17 @@ impl<'s> Scanner<'s> {
         Scanner {
             source_chars: itertools::peek_nth(source.char_indices()),
             source,
+            origin: Source::new(source),
+            errors: Vec::new(),
+            interpolations: Vec::new(),
         }
     }
 
+    /// Problems found so far, complete after [`Scanner::scan_tokens`] is exhausted
+    pub fn errors(&self) -> &[ScanError] {
+        &self.errors
+    }
+
     pub fn scan_tokens(&mut self) -> impl Iterator<Item = Token> {
         gen {
             while let Some((idx, ch)) = self.source_chars.next() {
@@ -32,31 +120,54 @@ impl<'s> Scanner<'s> {
                     ')' => Token::RightParen {
                         inner: TokenInner::new_right_paren(self.origin(), idx),
                     },
-                    '{' => Token::LeftBrace {
-                        inner: TokenInner::new_left_brace(self.origin(), idx),
-                    },
-                    '}' => Token::RightBrace {
-                        inner: TokenInner::new_right_brace(self.origin(), idx),
+                    '{' => {
+                        if let Some((_, depth)) = self.interpolations.last_mut() {
+                            *depth += 1;
+                        }
+                        Token::LeftBrace {
+                            inner: TokenInner::new_left_brace(self.origin(), idx),
+                        }
                     },
-                    ',' => Token::Comma {
-                        inner: TokenInner::new_comma(self.origin(), idx),
+                    '}' => match self.interpolations.last_mut() {
+                        // close the `${`, continue the string
+                        Some((_, 0)) => {
+                            self.interpolations.pop();
+                            self.parse_string(idx)
+                        },
+                        Some((_, depth)) => {
+                            *depth -= 1;
+                            Token::RightBrace {
+                                inner: TokenInner::new_right_brace(self.origin(), idx),
+                            }
+                        },
+                        None => Token::RightBrace {
+                            inner: TokenInner::new_right_brace(self.origin(), idx),
+                        },
                     },
-                    '.' => Token::Dot {
-                        inner: TokenInner::new_dot(self.origin(), idx),
+                    '[' => Token::LeftBracket {
+                        inner: TokenInner::new_left_bracket(self.origin(), idx),
                     },
-                    '-' => Token::Minus {
-                        inner: TokenInner::new_minus(self.origin(), idx),
+                    ']' => Token::RightBracket {
+                        inner: TokenInner::new_right_bracket(self.origin(), idx),
                     },
-                    '+' => Token::Plus {
-                        inner: TokenInner::new_plus(self.origin(), idx),
+                    ',' => Token::Comma {
+                        inner: TokenInner::new_comma(self.origin(), idx),
                     },
+                    '.' => self.parse_dot(idx),
                     ';' => Token::Semicolon {
                         inner: TokenInner::new_semicolon(self.origin(), idx),
                     },
-                    '*' => Token::Star {
-                        inner: TokenInner::new_star(self.origin(), idx),
+                    '?' => Token::Question {
+                        inner: TokenInner::new_question(self.origin(), idx),
+                    },
+                    ':' => Token::Colon {
+                        inner: TokenInner::new_colon(self.origin(), idx),
                     },
                     // > two char tokens
+                    '-' => self.parse_minus(idx),
+                    '+' => self.parse_plus(idx),
+                    '*' => self.parse_star(idx),
+                    '%' => self.parse_percent(idx),
                     '!' => self.parse_bang(idx),
                     '=' => self.parse_equal(idx),
                     '<' => self.parse_less(idx),
@@ -81,9 +192,26 @@ impl<'s> Scanner<'s> {
 
                 yield token;
             }
+
+            // `"...${ ...` until EOF
+            if let Some(&(idx, _)) = self.interpolations.first() {
+                self.interpolations.clear();
+                let inner = TokenInner::new_invalid(self.origin(), self.source.len() - idx, idx);
+                self.errors
+                    .push(ScanError::new(ScanErrorKind::UnterminatedString, inner));
+            }
         }
     }
 
+    /// Opt-in lossless mode, each token carries the comments and whitespace around it, so
+    /// the tokens written one by one give back the source byte for byte.
+    ///
+    /// The stream ends with a [`Token::Eof`] holding the trivia at the end of file
+    pub fn scan_lossless(&mut self) -> impl Iterator<Item = LosslessToken> {
+        let origin = self.origin();
+        attach_trivia(origin, self.scan_tokens())
+    }
+
     // pub fn scan_tokens(&mut self) -> Vec<Token> {
     //     let mut tokens = Vec::new();
     //     while let Some((idx, ch)) = self.source_chars.next() {
@@ -170,10 +298,38 @@ impl<'s> Scanner<'s> {
             "var" => Var { inner },
             "nil" => Nil { inner },
             "break" => Break { inner },
+            "continue" => Continue { inner },
+            "match" => Match { inner },
+            "in" => In { inner },
+            "trait" => Trait { inner },
+            "with" => With { inner },
+            "const" => Const { inner },
+            "import" => Import { inner },
+            "as" => As { inner },
+            "throw" => Throw { inner },
+            "try" => Try { inner },
+            "catch" => Catch { inner },
+            "finally" => Finally { inner },
+            "yield" => Yield { inner },
             _ => Identifier { inner },
         }
     }
 
+    /// ., ...
+    fn parse_dot(&mut self, idx: usize) -> Token {
+        if matches!(self.source_chars.peek(), Some((_, '.')))
+            && matches!(self.source_chars.peek_nth(1), Some((_, '.')))
+        {
+            self.source_chars.next();
+            self.source_chars.next();
+            return Token::DotDotDot {
+                inner: TokenInner::new_dot_dot_dot(self.origin(), idx),
+            };
+        }
+        Token::Dot {
+            inner: TokenInner::new_dot(self.origin(), idx),
+        }
+    }
     /// !, !=
     fn parse_bang(&mut self, idx: usize) -> Token {
         self.source_chars.next_if_eq(&(idx + 1, '=')).map_or_else(
@@ -185,8 +341,13 @@ impl<'s> Scanner<'s> {
             },
         )
     }
-    /// =, ==
+    /// =, ==, =>
     fn parse_equal(&mut self, idx: usize) -> Token {
+        if self.source_chars.next_if_eq(&(idx + 1, '>')).is_some() {
+            return Token::FatArrow {
+                inner: TokenInner::new_fat_arrow(self.origin(), idx),
+            };
+        }
         self.source_chars.next_if_eq(&(idx + 1, '=')).map_or_else(
             || Token::Equal {
                 inner: TokenInner::new_equal(self.origin(), idx),
@@ -219,7 +380,61 @@ impl<'s> Scanner<'s> {
         )
     }
 
-    /// /, //, /* ... */
+    /// -, -=, --
+    fn parse_minus(&mut self, idx: usize) -> Token {
+        match self.source_chars.next_if(|&(_, c)| c == '=' || c == '-') {
+            Some((_, '=')) => Token::MinusEqual {
+                inner: TokenInner::new_compound_equal(self.origin(), idx),
+            },
+            Some(_) => Token::MinusMinus {
+                inner: TokenInner::new_increment(self.origin(), idx),
+            },
+            None => Token::Minus {
+                inner: TokenInner::new_minus(self.origin(), idx),
+            },
+        }
+    }
+    /// +, +=, ++
+    fn parse_plus(&mut self, idx: usize) -> Token {
+        match self.source_chars.next_if(|&(_, c)| c == '=' || c == '+') {
+            Some((_, '=')) => Token::PlusEqual {
+                inner: TokenInner::new_compound_equal(self.origin(), idx),
+            },
+            Some(_) => Token::PlusPlus {
+                inner: TokenInner::new_increment(self.origin(), idx),
+            },
+            None => Token::Plus {
+                inner: TokenInner::new_plus(self.origin(), idx),
+            },
+        }
+    }
+    /// *, *=, **
+    fn parse_star(&mut self, idx: usize) -> Token {
+        match self.source_chars.next_if(|&(_, c)| c == '=' || c == '*') {
+            Some((_, '=')) => Token::StarEqual {
+                inner: TokenInner::new_compound_equal(self.origin(), idx),
+            },
+            Some(_) => Token::StarStar {
+                inner: TokenInner::new_star_star(self.origin(), idx),
+            },
+            None => Token::Star {
+                inner: TokenInner::new_star(self.origin(), idx),
+            },
+        }
+    }
+    /// %, %=
+    fn parse_percent(&mut self, idx: usize) -> Token {
+        self.source_chars.next_if_eq(&(idx + 1, '=')).map_or_else(
+            || Token::Percent {
+                inner: TokenInner::new_percent(self.origin(), idx),
+            },
+            |_eq| Token::PercentEqual {
+                inner: TokenInner::new_compound_equal(self.origin(), idx),
+            },
+        )
+    }
+
+    /// /, /=, //, /* ... */
     fn parse_slash(&mut self, idx: usize) -> Token {
         let slash = '/';
         match self.source_chars.next_if_eq(&(idx + 1, slash)) {
@@ -268,125 +483,177 @@ impl<'s> Scanner<'s> {
                             }
                         }
                         else {
-                            Token::Invalid {
-                                inner: TokenInner::new(self.origin(), self.source.len() - idx, idx),
-                            }
+                            self.invalid(
+                                ScanErrorKind::UnterminatedBlockComment,
+                                self.source.len() - idx,
+                                idx,
+                            )
                         }
                     },
-                    None => Token::Slash {
-                        inner: TokenInner::new_slash(self.origin(), idx),
-                    },
+                    None => self.source_chars.next_if_eq(&(idx + 1, '=')).map_or_else(
+                        || Token::Slash {
+                            inner: TokenInner::new_slash(self.origin(), idx),
+                        },
+                        |_eq| Token::SlashEqual {
+                            inner: TokenInner::new_compound_equal(self.origin(), idx),
+                        },
+                    ),
                 }
             },
         }
     }
 
-    /// `"..."`, `"...`, `"...\"...\\\n..."`
+    /// `"..."`, `"...`, `"...\"...\\\n..."`, `"...${`
+    ///
+    /// `idx` is the offset of the opening `"`, or of the `}` which closes an interpolated expression
     fn parse_string(&mut self, idx: usize) -> Token {
-        let mut res_len = 0;
-
-        let mut last_matched = '\0';
-        let mut need_escape = false;
-        let mut str_end = false;
-
-        loop {
-            let string: usize = self
-                .source_chars
-                .by_ref()
-                .take_while(|&(_, c)| {
-                    last_matched = c;
-                    if need_escape {
-                        need_escape = false;
-                        return true;
-                    }
-
-                    if c == '"' {
-                        str_end = true;
-                        false
-                    }
-                    else if c == '\\' {
-                        need_escape = true;
-                        false
-                    }
-                    else {
-                        true
-                    }
-                })
-                .map(|(_, c)| c.len_utf8())
-                .sum();
-
-            res_len += string;
-            if last_matched == '"' && str_end || self.source_chars.peek().is_none() {
-                break;
+        let mut value = String::new();
+
+        while let Some((pos, ch)) = self.source_chars.next() {
+            match ch {
+                '$' if self.source_chars.next_if_eq(&(pos + 1, '{')).is_some() => {
+                    self.interpolations.push((idx, 0));
+                    // with the "${"
+                    let len = pos + 2 - idx;
+                    return Token::Interpolation {
+                        inner: TokenInner::new_cooked(self.origin(), len, idx, &value),
+                    };
+                },
+                '"' => {
+                    // minus 1 trim start '"'
+                    let raw_len = pos - idx - 1;
+                    return Token::String {
+                        inner: TokenInner::new_escaped_string(self.origin(), raw_len, idx, &value),
+                    };
+                },
+                '\\' => match self.parse_escape() {
+                    Ok(escaped) => value.push(escaped),
+                    Err(end) => {
+                        let inner = TokenInner::new_invalid(self.origin(), end - pos, pos);
+                        self.errors
+                            .push(ScanError::new(ScanErrorKind::InvalidEscape, inner));
+                        value.push_str(&self.source[pos..end]);
+                    },
+                },
+                other => value.push(other),
             }
         }
 
-        match last_matched {
-            '"' => Token::String {
-                inner: TokenInner::new_string(self.origin(), res_len, idx),
-            },
-            // When does not end with '"' that may indicate EOF
-            _ => Token::Invalid {
-                inner: TokenInner::new(self.origin(), self.source.len() - idx, idx),
+        // When does not end with '"' that may indicate EOF
+        self.invalid(
+            ScanErrorKind::UnterminatedString,
+            self.source.len() - idx,
+            idx,
+        )
+    }
+
+    /// `\n`, `\t`, `\r`, `\\`, `\"`, `\0`, `\$`, `\u{XXXX}`, the `\` is consumed already.
+    ///
+    /// Return the end byte offset of the bad escape when it is invalid
+    fn parse_escape(&mut self) -> Result<char, usize> {
+        let Some((pos, ch)) = self.source_chars.next()
+        else {
+            return Err(self.source.len());
+        };
+        let end = pos + ch.len_utf8();
+        match ch {
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            'r' => Ok('\r'),
+            '\\' => Ok('\\'),
+            '"' => Ok('"'),
+            '0' => Ok('\0'),
+            '$' => Ok('$'),
+            'u' => {
+                if self.source_chars.next_if_eq(&(end, '{')).is_none() {
+                    return Err(end);
+                }
+                let mut code = 0_u32;
+                let mut digits = 0;
+                while let Some((_, hex)) =
+                    self.source_chars.next_if(|&(_, c)| c.is_ascii_hexdigit())
+                {
+                    // past 6 digits it's invalid anyway, only find where it ends
+                    if digits < 6 {
+                        code = code * 16 + hex.to_digit(16).unwrap_or_default();
+                    }
+                    digits += 1;
+                }
+                let closed = self.source_chars.next_if(|&(_, c)| c == '}').is_some();
+                let end = self.peek_offset();
+                if !closed || !(1..=6).contains(&digits) {
+                    return Err(end);
+                }
+                char::from_u32(code).ok_or(end)
             },
+            _ => Err(end),
         }
     }
 
-    fn parse_number(&mut self, first: char, idx: usize) -> Token {
-        let mut its_len = first.len_utf8();
+    /// Byte offset of the next char, or the source end
+    fn peek_offset(&mut self) -> usize {
+        self.source_chars
+            .peek()
+            .map_or(self.source.len(), |&(idx, _)| idx)
+    }
 
-        let mut count = 0;
-        while let Some(&(_, ch)) = self.source_chars.peek_nth(count)
-            && ch.is_ascii_digit()
+    /// `123`, `1.5`, `1_000`, `1e-9`, `2.5E+3`, `0xff`, `0b1010`, `0o17`
+    fn parse_number(&mut self, first: char, idx: usize) -> Token {
+        let mut radix = 10;
+        if first == '0'
+            && let Some(&(_, prefix)) = self.source_chars.peek()
         {
-            count += 1;
+            radix = match prefix {
+                'x' | 'X' => 16,
+                'b' | 'B' => 2,
+                'o' | 'O' => 8,
+                _ => 10,
+            };
+            if radix != 10 {
+                self.source_chars.next();
+            }
         }
 
-        let dig_integer: usize = self
-            .source_chars
-            .by_ref()
-            .take(count)
-            .map(|(_, c)| c.len_utf8())
-            .sum();
-        its_len += dig_integer;
-
-        // `take_while_ref` inner clone full iterator, so expensive
-        // let dig_integer = sour_chars
-        //     .take_while_ref(|&(_, ch)| ch.is_ascii_digit())
-        //     .map(|(_, c)| c)
-        //     .collect();
-
-        if let Some(&(_, next)) = self.source_chars.peek_nth(0)
-            && let Some(&(_, next_next)) = self.source_chars.peek_nth(1)
-            && next == '.'
-            && next_next.is_ascii_digit()
-        {
-            let (_, _dot) = unsafe { self.source_chars.next().unwrap_unchecked() };
-
-            let mut count = 0;
-
-            while let Some(&(_, ch)) = self.source_chars.peek_nth(count)
-                && ch.is_ascii_digit()
+        // take the whole literal, check it later, so `12ab` is one bad number
+        let (mut seen_dot, mut seen_exp) = (false, false);
+        while let Some(&(_, ch)) = self.source_chars.peek() {
+            if radix == 10 && matches!(ch, 'e' | 'E') && !seen_exp {
+                seen_exp = true;
+                self.source_chars.next();
+                self.source_chars.next_if(|&(_, c)| matches!(c, '+' | '-'));
+            }
+            else if ch.is_ascii_alphanumeric() || ch == '_' {
+                self.source_chars.next();
+            }
+            else if radix == 10
+                && ch == '.'
+                && !seen_dot
+                && !seen_exp
+                && let Some(&(_, next)) = self.source_chars.peek_nth(1)
+                && next.is_ascii_digit()
             {
-                count += 1;
+                seen_dot = true;
+                self.source_chars.next();
+            }
+            else {
+                break;
             }
-
-            let decimal: usize = self
-                .source_chars
-                .by_ref()
-                .take(count)
-                .map(|(_, c)| c.len_utf8())
-                .sum();
-            its_len += decimal;
-            its_len += '.'.len_utf8();
         }
+        let len = self.peek_offset() - idx;
 
-        let inner = TokenInner::new(self.origin(), its_len, idx);
-
-        Token::Number {
-            // Safety: the previous scan must have output a float
-            double: unsafe { inner.lexeme().parse().unwrap_unchecked() },
-            inner,
+        match number_value(&self.source[idx..idx + len], radix) {
+            Ok(double) => Token::Number {
+                double,
+                inner: TokenInner::new(self.origin(), len, idx),
+            },
+            Err((err, bad)) => {
+                let inner = TokenInner::new_invalid(self.origin(), bad.len(), idx + bad.start);
+                self.errors
+                    .push(ScanError::new(ScanErrorKind::MalformedNumber(err), inner));
+                Token::Invalid {
+                    inner: TokenInner::new_invalid(self.origin(), len, idx),
+                }
+            },
         }
     }
 
@@ -426,16 +693,85 @@ impl<'s> Scanner<'s> {
             .map(|(_, c)| c.len_utf8())
             .sum();
         len += ot;
-        Token::Invalid {
-            inner: TokenInner::new(self.origin(), len, idx),
-        }
+        self.invalid(ScanErrorKind::UnexpectedChar, len, idx)
+    }
+
+    /// Record the error and give a [`Token::Invalid`] in place of the bad code
+    fn invalid(&mut self, kind: ScanErrorKind, len: usize, idx: usize) -> Token {
+        let inner = TokenInner::new_invalid(self.origin(), len, idx);
+        self.errors.push(ScanError::new(kind, inner.clone()));
+        Token::Invalid { inner }
     }
 
-    pub fn origin(&self) -> Rc<str> {
-        Rc::from(self.source)
+    pub fn origin(&self) -> Source {
+        self.origin.clone()
     }
 
     pub const fn source(&self) -> &str {
         self.source
     }
 }
+
+/// The value of a number literal, or what's wrong and where (relative to `text`)
+fn number_value(text: &str, radix: u32) -> Result<f64, (NumberError, Range<usize>)> {
+    if radix != 10 {
+        check_digits(text, 2..text.len(), radix, 0..2)?;
+        let double = text[2..]
+            .chars()
+            .filter_map(|c| c.to_digit(radix))
+            .fold(0.0, |acc: f64, d| {
+                acc.mul_add(f64::from(radix), f64::from(d))
+            });
+        return Ok(double);
+    }
+
+    let exp = text.find(['e', 'E']);
+    let mantissa_end = exp.unwrap_or(text.len());
+    match text[..mantissa_end].find('.') {
+        Some(dot) => {
+            check_digits(text, 0..dot, 10, dot..dot + 1)?;
+            check_digits(text, dot + 1..mantissa_end, 10, dot..dot + 1)?;
+        },
+        None => check_digits(text, 0..mantissa_end, 10, 0..mantissa_end)?,
+    }
+    if let Some(exp) = exp {
+        let sign = usize::from(text[exp + 1..].starts_with(['+', '-']));
+        check_digits(text, exp + 1 + sign..text.len(), 10, exp..exp + 1 + sign)?;
+    }
+
+    let digits: String = text.chars().filter(|&c| c != '_').collect();
+    digits
+        .parse()
+        .map_err(|_e| (NumberError::InvalidDigit, 0..text.len()))
+}
+
+/// `text[range]` is digits of `radix` joined by single `_`, report `marker` when it's empty
+fn check_digits(
+    text: &str,
+    range: Range<usize>,
+    radix: u32,
+    marker: Range<usize>,
+) -> Result<(), (NumberError, Range<usize>)> {
+    let digits = &text[range.clone()];
+    if digits.is_empty() {
+        return Err((NumberError::MissingDigits, marker));
+    }
+    let is_digit = |c: Option<char>| c.is_some_and(|c| c.is_digit(radix));
+    for (i, c) in digits.char_indices() {
+        let pos = range.start + i;
+        if c == '_' {
+            if !is_digit(digits[..i].chars().next_back())
+                || !is_digit(digits[i + 1..].chars().next())
+            {
+                return Err((NumberError::MisplacedSeparator, pos..pos + 1));
+            }
+        }
+        else if !c.is_digit(radix) {
+            let bad = digits[i..]
+                .find(|c: char| c.is_digit(radix) || c == '_')
+                .map_or(range.end, |j| pos + j);
+            return Err((NumberError::InvalidDigit, pos..bad));
+        }
+    }
+    Ok(())
+}
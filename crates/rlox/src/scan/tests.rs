@@ -3,7 +3,10 @@ use std::rc::Rc;
 use pretty_assertions::assert_eq;
 
 use crate::{
-    scan::scanner::Scanner,
+    scan::{
+        scanner::{NumberError, ScanError, ScanErrorKind, Scanner},
+        trivia::{LosslessToken, TriviaKind},
+    },
     token::{Token, TokenInner},
 };
 
@@ -19,77 +22,111 @@ fn test_scan_missing_paren() {
 
 #[test]
 fn test_scan_string_escape() {
-    let source = Rc::from("\"abcd\\\"\\\"\n\t\refg\";");
-    let correct = vec![
-        Token::String {
-            inner: TokenInner::new_string(Rc::clone(&source), "abcd\"\"\n\t\refg".len(), 0),
-        },
-        Token::Semicolon {
-            inner: TokenInner::new_semicolon(Rc::clone(&source), 16),
-        },
+    let cases = [
+        ("\"abcd\\\"\\\"\n\t\refg\";", "abcd\"\"\n\t\refg"),
+        ("\"abcd\\\"\\\"\nefg\";", "abcd\"\"\nefg"),
+        (r#""abcd\"\"efg";"#, r#"abcd""efg"#),
+        (r#""abcd\"efg";"#, r#"abcd"efg"#),
+        (r#""abcd\\\"efg";"#, r#"abcd\"efg"#),
+        (r#""abcd\\#efg";"#, r#"abcd\#efg"#),
+        (r#""a\nb\tc\rd\0e";"#, "a\nb\tc\rd\0e"),
+        (r#""\u{4E2D}\u{6587}\u{1F600}";"#, "中文😀"),
     ];
-    let mut sc = Scanner::new(&source);
-    assert_eq!(sc.scan_tokens().collect::<Vec<_>>(), correct);
-
-    let source = Rc::from("\"abcd\\\"\\\"\nefg\";");
-    let correct = vec![
-        Token::String {
-            inner: TokenInner::new_string(Rc::clone(&source), "abcd\"\"\nefg".len(), 0),
-        },
-        Token::Semicolon {
-            inner: TokenInner::new_semicolon(Rc::clone(&source), 14),
-        },
-    ];
-    let mut sc = Scanner::new(&source);
-    assert_eq!(sc.scan_tokens().collect::<Vec<_>>(), correct);
+    for (source, value) in cases {
+        let source: Rc<str> = Rc::from(source);
+        let semicolon = source.len() - 1;
+        let correct = vec![
+            Token::String {
+                inner: TokenInner::new_escaped_string(Rc::clone(&source), semicolon - 2, 0, value),
+            },
+            Token::Semicolon {
+                inner: TokenInner::new_semicolon(Rc::clone(&source), semicolon),
+            },
+        ];
+        let mut sc = Scanner::new(&source);
+        assert_eq!(sc.scan_tokens().collect::<Vec<_>>(), correct);
+        assert!(sc.errors().is_empty());
+    }
+}
 
-    let source = Rc::from(r#""abcd\"\"efg";"#);
-    let correct = vec![
-        Token::String {
-            inner: TokenInner::new_string(Rc::clone(&source), r#"abcd""efg"#.len(), 0),
-        },
-        Token::Semicolon {
-            inner: TokenInner::new_semicolon(Rc::clone(&source), 13),
-        },
-    ];
-    let mut sc = Scanner::new(&source);
-    assert_eq!(sc.scan_tokens().collect::<Vec<_>>(), correct);
+#[test]
+fn test_scan_interpolation() {
+    let source = r#""a ${b + "${c}"} d ${ {} } \${e}";"#;
+    let mut sc = Scanner::new(source);
+    let tokens: Vec<_> = sc
+        .scan_tokens()
+        .map(|t| {
+            let kind = match t {
+                Token::Interpolation { .. } => "interpolation",
+                Token::String { .. } => "string",
+                Token::LeftBrace { .. } => "{",
+                Token::RightBrace { .. } => "}",
+                _ => "other",
+            };
+            (
+                kind,
+                t.inner().lexeme().to_owned(),
+                &source[t.inner().span()],
+            )
+        })
+        .collect();
+    assert!(sc.errors().is_empty());
+    assert_eq!(
+        tokens,
+        [
+            ("interpolation", "a ".to_owned(), r#""a ${"#),
+            ("other", "b".to_owned(), "b"),
+            ("other", "+".to_owned(), "+"),
+            ("interpolation", String::new(), r#""${"#),
+            ("other", "c".to_owned(), "c"),
+            ("string", String::new(), r#"}""#),
+            ("interpolation", " d ".to_owned(), "} d ${"),
+            ("{", "{".to_owned(), "{"),
+            ("}", "}".to_owned(), "}"),
+            ("string", " ${e}".to_owned(), r#"} \${e}""#),
+            ("other", ";".to_owned(), ";"),
+        ]
+    );
 
-    let source = Rc::from(r#""abcd\"efg";"#);
-    let correct = vec![
-        Token::String {
-            inner: TokenInner::new_string(Rc::clone(&source), r#"abcd"efg"#.len(), 0),
-        },
-        Token::Semicolon {
-            inner: TokenInner::new_semicolon(Rc::clone(&source), 11),
-        },
-    ];
-    let mut sc = Scanner::new(&source);
-    assert_eq!(sc.scan_tokens().collect::<Vec<_>>(), correct);
+    let source = r#""a ${b"#;
+    let mut sc = Scanner::new(source);
+    sc.scan_tokens().for_each(drop);
+    assert_eq!(
+        sc.errors().iter().map(ScanError::kind).collect::<Vec<_>>(),
+        [ScanErrorKind::UnterminatedString]
+    );
+}
 
-    let source = Rc::from(r#""abcd\\\"efg";"#);
-    let correct = vec![
-        Token::String {
-            inner: TokenInner::new_string(Rc::clone(&source), r#"abcd\"efg"#.len(), 0),
-        },
-        Token::Semicolon {
-            inner: TokenInner::new_semicolon(Rc::clone(&source), 13),
-        },
-    ];
-    let mut sc = Scanner::new(&source);
-    assert_eq!(sc.scan_tokens().collect::<Vec<_>>(), correct);
+#[test]
+fn test_scan_string_bad_escape() {
+    let source = r#"print "a\qb\u{110000}\u4E2D\u{}\u{1234567}\u{FFFFFFFFF}";"#;
+    let mut sc = Scanner::new(source);
+    let tokens: Vec<_> = sc.scan_tokens().collect();
+    assert!(matches!(tokens[1], Token::String { .. }));
+    let errors: Vec<_> = sc
+        .errors()
+        .iter()
+        .map(|e| (e.kind(), &source[e.span()]))
+        .collect();
+    assert_eq!(
+        errors,
+        [
+            (ScanErrorKind::InvalidEscape, r"\q"),
+            (ScanErrorKind::InvalidEscape, r"\u{110000}"),
+            (ScanErrorKind::InvalidEscape, r"\u"),
+            (ScanErrorKind::InvalidEscape, r"\u{}"),
+            (ScanErrorKind::InvalidEscape, r"\u{1234567}"),
+            (ScanErrorKind::InvalidEscape, r"\u{FFFFFFFFF}"),
+        ]
+    );
 
-    let source = Rc::from(r#""abcd\\#efg";"#);
-    let correct = vec![
-        Token::String {
-            inner: TokenInner::new_string(Rc::clone(&source), r#"abcd\#efg"#.len(), 0),
-        },
-        Token::Semicolon {
-            inner: TokenInner::new_semicolon(Rc::clone(&source), 12),
-        },
-    ];
-    let mut sc = Scanner::new(&source);
-    assert_eq!(sc.scan_tokens().collect::<Vec<_>>(), correct);
+    let source = r#""abc\"#;
+    let mut sc = Scanner::new(source);
+    sc.scan_tokens().for_each(drop);
+    assert_eq!(
+        sc.errors().last().map(ScanError::kind),
+        Some(ScanErrorKind::UnterminatedString)
+    );
 }
 
 #[test]
@@ -186,6 +223,28 @@ fn test_line_col() {
     }
 }
 
+#[test]
+fn test_line_col_span() {
+    let source = "var a = \"中文\";\n  print a;\r\n\n// end\nb";
+    let mut sc = Scanner::new(source);
+    let tokens: Vec<_> = sc.scan_tokens().collect();
+
+    let string = tokens[3].inner();
+    assert_eq!(string.get_xy(), (1, 9));
+    assert_eq!(&source[string.span()], "\"中文\"");
+
+    let semicolon = tokens[4].inner();
+    assert_eq!(semicolon.get_xy(), (1, 13));
+
+    let print = tokens[5].inner();
+    assert_eq!(print.get_xy(), (2, 3));
+    assert_eq!(print.line(), 2);
+    assert_eq!(&source[print.span()], "print");
+
+    let b = tokens.last().unwrap().inner();
+    assert_eq!(b.get_xy(), (5, 1));
+}
+
 #[test]
 fn test_maximal_munch() {
     let source = Rc::from("var vara");
@@ -652,3 +711,199 @@ var seven = 1 >= 2;
     let mut sc = Scanner::new(&source);
     assert_eq!(sc.scan_tokens().collect::<Vec<_>>(), correct);
 }
+
+#[test]
+fn test_scan_compound_assign() {
+    let source = Rc::from("a+=1-=b*=c/=d%=e/ -");
+    let correct = vec![
+        Token::Identifier {
+            inner: TokenInner::new(Rc::clone(&source), "a".len(), 0),
+        },
+        Token::PlusEqual {
+            inner: TokenInner::new_compound_equal(Rc::clone(&source), 1),
+        },
+        Token::Number {
+            double: 1.0,
+            inner: TokenInner::new(Rc::clone(&source), "1".len(), 3),
+        },
+        Token::MinusEqual {
+            inner: TokenInner::new_compound_equal(Rc::clone(&source), 4),
+        },
+        Token::Identifier {
+            inner: TokenInner::new(Rc::clone(&source), "b".len(), 6),
+        },
+        Token::StarEqual {
+            inner: TokenInner::new_compound_equal(Rc::clone(&source), 7),
+        },
+        Token::Identifier {
+            inner: TokenInner::new(Rc::clone(&source), "c".len(), 9),
+        },
+        Token::SlashEqual {
+            inner: TokenInner::new_compound_equal(Rc::clone(&source), 10),
+        },
+        Token::Identifier {
+            inner: TokenInner::new(Rc::clone(&source), "d".len(), 12),
+        },
+        Token::PercentEqual {
+            inner: TokenInner::new_compound_equal(Rc::clone(&source), 13),
+        },
+        Token::Identifier {
+            inner: TokenInner::new(Rc::clone(&source), "e".len(), 15),
+        },
+        Token::Slash {
+            inner: TokenInner::new_slash(Rc::clone(&source), 16),
+        },
+        Token::Minus {
+            inner: TokenInner::new_minus(Rc::clone(&source), 18),
+        },
+    ];
+
+    let mut sc = Scanner::new(&source);
+    assert_eq!(sc.scan_tokens().collect::<Vec<_>>(), correct);
+}
+
+#[test]
+fn test_scan_number_forms() {
+    let cases = [
+        ("0xff", 255.0),
+        ("0XFF", 255.0),
+        ("0b1010", 10.0),
+        ("0o17", 15.0),
+        ("1_000_000", 1_000_000.0),
+        ("1e-9", 1e-9),
+        ("2.5E+3", 2500.0),
+        ("1_0.2_5e1_0", 10.25e10),
+        ("007", 7.0),
+    ];
+    for (source, value) in cases {
+        let tokens: Vec<_> = Scanner::new(source).scan_tokens().collect();
+        match tokens.as_slice() {
+            [Token::Number { double, inner }] => {
+                assert_eq!(*double, value, "{source}");
+                assert_eq!(inner.lexeme(), source);
+            },
+            other => panic!("{source}: {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn test_scan_number_errors() {
+    let cases = [
+        ("0x", NumberError::MissingDigits, "0x"),
+        ("1e", NumberError::MissingDigits, "e"),
+        ("1e+", NumberError::MissingDigits, "e+"),
+        ("0b102", NumberError::InvalidDigit, "2"),
+        ("0o8", NumberError::InvalidDigit, "8"),
+        ("12ab", NumberError::InvalidDigit, "ab"),
+        ("1e5x", NumberError::InvalidDigit, "x"),
+        ("1__0", NumberError::MisplacedSeparator, "_"),
+        ("1_", NumberError::MisplacedSeparator, "_"),
+        ("0x_f", NumberError::MisplacedSeparator, "_"),
+        ("1_.5", NumberError::MisplacedSeparator, "_"),
+    ];
+    for (source, err, bad) in cases {
+        let mut sc = Scanner::new(source);
+        let tokens: Vec<_> = sc.scan_tokens().collect();
+        assert!(
+            matches!(tokens.as_slice(), [Token::Invalid { inner }] if inner.lexeme() == source),
+            "{source}: {tokens:?}"
+        );
+        let errors: Vec<_> = sc
+            .errors()
+            .iter()
+            .map(|e| (e.kind(), &source[e.span()]))
+            .collect();
+        assert_eq!(
+            errors,
+            [(ScanErrorKind::MalformedNumber(err), bad)],
+            "{source}"
+        );
+    }
+}
+
+#[test]
+fn test_scan_errors() {
+    let source = "var a = 1 # 2;\nvar b = 12ab;\n/* open\nprint \"open;";
+    let mut sc = Scanner::new(source);
+    let tokens: Vec<_> = sc.scan_tokens().collect();
+    assert!(tokens.iter().any(|tk| matches!(tk, Token::Invalid { .. })));
+
+    let errors: Vec<_> = sc
+        .errors()
+        .iter()
+        .map(|e| (e.kind(), e.span(), e.inner().get_xy()))
+        .collect();
+    assert_eq!(
+        errors,
+        [
+            (ScanErrorKind::UnexpectedChar, 10..11, (1, 11)),
+            (
+                ScanErrorKind::MalformedNumber(NumberError::InvalidDigit),
+                25..27,
+                (2, 11)
+            ),
+            (
+                ScanErrorKind::UnterminatedBlockComment,
+                29..source.len(),
+                (3, 1)
+            ),
+        ]
+    );
+
+    let source = "print \"open;";
+    let mut sc = Scanner::new(source);
+    sc.scan_tokens().for_each(drop);
+    assert_eq!(sc.errors().len(), 1);
+    assert_eq!(sc.errors()[0].kind(), ScanErrorKind::UnterminatedString);
+    assert_eq!(sc.errors()[0].span(), 6..source.len());
+}
+
+#[test]
+fn test_scan_lossless() {
+    let source = "// head\nvar a = 1; // one\n\n/* block\n */ print \"x\\n${a}\" ;  \n/* open";
+    let mut sc = Scanner::new(source);
+    let tokens: Vec<_> = sc.scan_lossless().collect();
+    assert_eq!(
+        tokens.iter().map(ToString::to_string).collect::<String>(),
+        source
+    );
+
+    let semicolon = &tokens[4];
+    assert_eq!(semicolon.raw(), ";");
+    assert_eq!(
+        semicolon
+            .trailing()
+            .iter()
+            .map(|t| (t.kind(), t.text()))
+            .collect::<Vec<_>>(),
+        [
+            (TriviaKind::Whitespace, " "),
+            (TriviaKind::LineComment, "// one")
+        ]
+    );
+    let print = &tokens[5];
+    assert_eq!(
+        print
+            .leading()
+            .iter()
+            .map(|t| (t.kind(), t.text()))
+            .collect::<Vec<_>>(),
+        [
+            (TriviaKind::Whitespace, "\n\n"),
+            (TriviaKind::BlockComment, "/* block\n */"),
+            (TriviaKind::Whitespace, " "),
+        ]
+    );
+    assert!(matches!(
+        tokens.last().map(LosslessToken::token),
+        Some(Token::Eof { .. })
+    ));
+
+    for entry in std::fs::read_dir("test-resource/ok").unwrap() {
+        let source = std::fs::read_to_string(entry.unwrap().path()).unwrap();
+        let mut sc = Scanner::new(&source);
+        let lossless: String = sc.scan_lossless().map(|t| t.to_string()).collect();
+        assert_eq!(lossless, source);
+    }
+}
@@ -0,0 +1,181 @@
+use std::{fmt::Display, ops::Range};
+
+use crate::token::{Source, Token, TokenInner};
+
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+pub enum TriviaKind {
+    /// spaces, tabs, newlines
+    Whitespace,
+    /// `// ...`, without the ending newline
+    LineComment,
+    /// `/* ... */`
+    BlockComment,
+}
+
+/// Code between tokens that doesn't change the meaning of the program
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+pub struct Trivia {
+    kind: TriviaKind,
+    inner: TokenInner,
+}
+
+impl Trivia {
+    pub const fn kind(&self) -> TriviaKind {
+        self.kind
+    }
+
+    pub fn text(&self) -> &str {
+        self.inner.lexeme()
+    }
+
+    pub const fn span(&self) -> Range<usize> {
+        self.inner.span()
+    }
+}
+
+/// A token with the trivia around it.
+///
+/// The trailing trivia runs until the end of the line, the rest belongs to the next token's
+/// leading trivia. The trivia left at the end of file is the leading trivia of [`Token::Eof`].
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+pub struct LosslessToken {
+    leading: Vec<Trivia>,
+    token: Token,
+    trailing: Vec<Trivia>,
+}
+
+impl LosslessToken {
+    pub const fn token(&self) -> &Token {
+        &self.token
+    }
+
+    pub fn into_token(self) -> Token {
+        self.token
+    }
+
+    pub fn leading(&self) -> &[Trivia] {
+        &self.leading
+    }
+
+    pub fn trailing(&self) -> &[Trivia] {
+        &self.trailing
+    }
+
+    /// The token code as written in the source, a string's lexeme is the escaped value
+    pub fn raw(&self) -> &str {
+        let inner = self.token.inner();
+        &inner.origin().text()[inner.span()]
+    }
+}
+
+impl Display for LosslessToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for trivia in &self.leading {
+            f.write_str(trivia.text())?;
+        }
+        f.write_str(self.raw())?;
+        for trivia in &self.trailing {
+            f.write_str(trivia.text())?;
+        }
+        Ok(())
+    }
+}
+
+/// Attach the code between `tokens` to them, the source must be the one of the tokens
+pub fn attach_trivia<I>(origin: Source, tokens: I) -> impl Iterator<Item = LosslessToken>
+where
+    I: IntoIterator<Item = Token>,
+{
+    gen move {
+        let len = origin.text().len();
+        let mut pending: Option<LosslessToken> = None;
+        let mut last_end = 0;
+        let eof = Token::Eof {
+            inner: TokenInner::new(origin.clone(), 0, len),
+        };
+
+        for token in tokens.into_iter().chain([eof]) {
+            let span = token.inner().span();
+            let mut trivia = split_trivia(&origin, last_end..span.start);
+            last_end = span.end;
+
+            if let Some(mut prev) = pending.take() {
+                let at_newline = trivia
+                    .iter()
+                    .position(|t| t.text().contains('\n'))
+                    .unwrap_or(trivia.len());
+                let mut rest = trivia.split_off(at_newline);
+                // keep the spaces before the newline on this line
+                if let Some(first) = rest.first()
+                    && first.kind == TriviaKind::Whitespace
+                    && let Some(nl) = first.text().find('\n')
+                    && nl > 0
+                {
+                    let start = first.span().start;
+                    let end = first.span().end;
+                    trivia.push(whitespace(&origin, start..start + nl));
+                    rest[0] = whitespace(&origin, start + nl..end);
+                }
+                prev.trailing = trivia;
+                yield prev;
+                trivia = rest;
+            }
+            pending = Some(LosslessToken {
+                leading: trivia,
+                token,
+                trailing: vec![],
+            });
+        }
+
+        if let Some(eof) = pending {
+            yield eof;
+        }
+    }
+}
+
+fn whitespace(origin: &Source, range: Range<usize>) -> Trivia {
+    Trivia {
+        kind: TriviaKind::Whitespace,
+        inner: TokenInner::new(origin.clone(), range.len(), range.start),
+    }
+}
+
+/// Split the code between two tokens, only whitespace and comments can be there
+fn split_trivia(origin: &Source, range: Range<usize>) -> Vec<Trivia> {
+    let text = &origin.text()[range.clone()];
+    let mut res = Vec::new();
+    let mut pos = 0;
+    while pos < text.len() {
+        let rest = &text[pos..];
+        let (kind, len) = if rest.starts_with("//") {
+            (
+                TriviaKind::LineComment,
+                rest.find('\n').unwrap_or(rest.len()),
+            )
+        }
+        else if let Some(body) = rest.strip_prefix("/*") {
+            let len = body.find("*/").map_or(rest.len(), |end| end + 4);
+            (TriviaKind::BlockComment, len)
+        }
+        else {
+            let len = rest
+                .char_indices()
+                .skip(1)
+                .find(|&(_, c)| c == '/')
+                .map_or(rest.len(), |(i, _)| i);
+            (TriviaKind::Whitespace, len)
+        };
+        res.push(Trivia {
+            kind,
+            inner: TokenInner::new(origin.clone(), len, range.start + pos),
+        });
+        pos += len;
+    }
+    res
+}
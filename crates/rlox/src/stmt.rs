@@ -1,5 +1,6 @@
 use crate::{
-    expr::{Exprs, Variable},
+    expr::{Exprs, LiteralType, Variable},
+    span::Span,
     token::Token,
 };
 
@@ -13,7 +14,14 @@ pub trait Stmt {
 pub struct Class {
     name: Token,
     superclass: Option<Variable>,
+    /// `with A, B`, in declaration order
+    traits: Vec<Variable>,
     methods: Vec<Function>, // Stmts::Function
+    /// `class fun name() {}`, called on the class itself
+    static_methods: Vec<Function>,
+    /// `fun name {}`, run on property access
+    getters: Vec<Function>,
+    span: Span,
 }
 
 impl Class {
@@ -25,10 +33,32 @@ impl Class {
         Self {
             name,
             superclass: superclass.into(),
+            traits: Vec::new(),
             methods,
+            static_methods: Vec::new(),
+            getters: Vec::new(),
+            span: Span::new(0, 0),
         }
     }
 
+    #[must_use]
+    pub fn with_traits(mut self, traits: Vec<Variable>) -> Self {
+        self.traits = traits;
+        self
+    }
+
+    #[must_use]
+    pub fn with_static_methods(mut self, static_methods: Vec<Function>) -> Self {
+        self.static_methods = static_methods;
+        self
+    }
+
+    #[must_use]
+    pub fn with_getters(mut self, getters: Vec<Function>) -> Self {
+        self.getters = getters;
+        self
+    }
+
     pub const fn name(&self) -> &Token {
         &self.name
     }
@@ -37,9 +67,194 @@ impl Class {
         &self.methods
     }
 
+    pub fn static_methods(&self) -> &[Function] {
+        &self.static_methods
+    }
+
+    pub fn getters(&self) -> &[Function] {
+        &self.getters
+    }
+
     pub const fn superclass(&self) -> Option<&Variable> {
         self.superclass.as_ref()
     }
+
+    pub fn traits(&self) -> &[Variable] {
+        &self.traits
+    }
+}
+
+/// `throw value;`
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq, Hash)]
+pub struct Throw {
+    keyword: Token,
+    value: Exprs,
+    span: Span,
+}
+
+impl Throw {
+    pub const fn new(keyword: Token, value: Exprs) -> Self {
+        Self {
+            keyword,
+            value,
+            span: Span::new(0, 0),
+        }
+    }
+
+    pub const fn keyword(&self) -> &Token {
+        &self.keyword
+    }
+
+    pub const fn value(&self) -> &Exprs {
+        &self.value
+    }
+}
+
+/// `try { } catch (e) { } finally { }`, at least one of `catch` and `finally`
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq, Hash)]
+pub struct Try {
+    keyword: Token,
+    body: Vec<Stmts>,
+    catch: Option<CatchClause>,
+    finally: Option<Vec<Stmts>>,
+    span: Span,
+}
+
+impl Try {
+    pub const fn new(
+        keyword: Token,
+        body: Vec<Stmts>,
+        catch: Option<CatchClause>,
+        finally: Option<Vec<Stmts>>,
+    ) -> Self {
+        Self {
+            keyword,
+            body,
+            catch,
+            finally,
+            span: Span::new(0, 0),
+        }
+    }
+
+    pub const fn keyword(&self) -> &Token {
+        &self.keyword
+    }
+
+    pub fn body(&self) -> &[Stmts] {
+        &self.body
+    }
+
+    pub const fn catch(&self) -> Option<&CatchClause> {
+        self.catch.as_ref()
+    }
+
+    pub fn finally(&self) -> Option<&[Stmts]> {
+        self.finally.as_deref()
+    }
+}
+
+/// `catch (binding) { body }`
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq, Hash)]
+pub struct CatchClause {
+    pub binding: Token,
+    pub body: Vec<Stmts>,
+}
+
+/// `import "path.lox" as name;`
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq, Hash)]
+pub struct Import {
+    keyword: Token,
+    /// the string token, relative to the importing file
+    path: Token,
+    name: Token,
+    span: Span,
+}
+
+impl Import {
+    pub const fn new(keyword: Token, path: Token, name: Token) -> Self {
+        Self {
+            keyword,
+            path,
+            name,
+            span: Span::new(0, 0),
+        }
+    }
+
+    pub const fn keyword(&self) -> &Token {
+        &self.keyword
+    }
+
+    pub const fn path(&self) -> &Token {
+        &self.path
+    }
+
+    pub const fn name(&self) -> &Token {
+        &self.name
+    }
+}
+
+/// `trait Name { fun method() {} }`, the methods are copied into classes using it
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq, Hash)]
+pub struct Trait {
+    name: Token,
+    methods: Vec<Function>,
+    span: Span,
+}
+
+impl Trait {
+    pub const fn new(name: Token, methods: Vec<Function>) -> Self {
+        Self {
+            name,
+            methods,
+            span: Span::new(0, 0),
+        }
+    }
+
+    pub const fn name(&self) -> &Token {
+        &self.name
+    }
+
+    pub fn methods(&self) -> &[Function] {
+        &self.methods
+    }
+}
+
+/// `yield value;`, suspends the generator the function call returned
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq, Hash)]
+pub struct Yield {
+    keyword: Token,
+    value: Option<Exprs>,
+    span: Span,
+}
+
+impl Yield {
+    pub fn new<V: Into<Option<Exprs>>>(keyword: Token, value: V) -> Self {
+        Self {
+            keyword,
+            value: value.into(),
+            span: Span::new(0, 0),
+        }
+    }
+
+    pub const fn value(&self) -> Option<&Exprs> {
+        self.value.as_ref()
+    }
+
+    pub const fn keyword(&self) -> &Token {
+        &self.keyword
+    }
 }
 
 #[derive(Clone)]
@@ -48,6 +263,7 @@ impl Class {
 pub struct Return {
     keyword: Token,
     value: Option<Exprs>,
+    span: Span,
 }
 
 impl Return {
@@ -55,6 +271,7 @@ impl Return {
         Self {
             keyword,
             value: value.into(),
+            span: Span::new(0, 0),
         }
     }
 
@@ -73,12 +290,46 @@ impl Return {
 pub struct Function {
     pub name: Token,
     pub params: Vec<Token>,
+    /// Default values of the trailing params, `fun f(a, b = 1)`
+    pub defaults: Vec<Exprs>,
+    /// `...rest` gets the extra arguments as a list
+    pub rest: Option<Token>,
     pub body: Vec<Stmts>,
+    /// The body has a `yield`, a call returns a generator instead of running it
+    pub is_generator: bool,
+    span: Span,
 }
 
 impl Function {
-    pub const fn new(name: Token, params: Vec<Token>, body: Vec<Stmts>) -> Self {
-        Self { name, params, body }
+    pub fn new(name: Token, params: Vec<Token>, body: Vec<Stmts>) -> Self {
+        Self {
+            name,
+            params,
+            defaults: Vec::new(),
+            rest: None,
+            is_generator: body.iter().any(Stmts::has_yield),
+            body,
+            span: Span::new(0, 0),
+        }
+    }
+
+    #[must_use]
+    pub fn with_defaults(mut self, defaults: Vec<Exprs>) -> Self {
+        self.defaults = defaults;
+        self
+    }
+
+    #[must_use]
+    pub fn with_rest(mut self, rest: Token) -> Self {
+        self.rest = Some(rest);
+        self
+    }
+
+    /// The default value of `params[idx]`
+    pub fn default_of(&self, idx: usize) -> Option<&Exprs> {
+        let required = self.params.len() - self.defaults.len();
+        idx.checked_sub(required)
+            .and_then(|idx| self.defaults.get(idx))
     }
 }
 
@@ -87,11 +338,15 @@ impl Function {
 #[derive(PartialEq, Eq, Hash)]
 pub struct Expression {
     expr: Exprs,
+    span: Span,
 }
 
 impl Expression {
     pub const fn new(expr: Exprs) -> Self {
-        Self { expr }
+        Self {
+            expr,
+            span: Span::new(0, 0),
+        }
     }
 }
 
@@ -100,11 +355,15 @@ impl Expression {
 #[derive(PartialEq, Eq, Hash)]
 pub struct Print {
     expr: Exprs,
+    span: Span,
 }
 
 impl Print {
     pub const fn new(expr: Exprs) -> Self {
-        Self { expr }
+        Self {
+            expr,
+            span: Span::new(0, 0),
+        }
     }
 }
 
@@ -114,11 +373,29 @@ impl Print {
 pub struct Var {
     name: Token,
     expr: Option<Exprs>,
+    /// `const name = expr;`, the resolver rejects assignments to it
+    is_const: bool,
+    span: Span,
 }
 
 impl Var {
     pub const fn new(name: Token, expr: Option<Exprs>) -> Self {
-        Self { name, expr }
+        Self {
+            name,
+            expr,
+            is_const: false,
+            span: Span::new(0, 0),
+        }
+    }
+
+    #[must_use]
+    pub const fn with_const(mut self, is_const: bool) -> Self {
+        self.is_const = is_const;
+        self
+    }
+
+    pub const fn is_const(&self) -> bool {
+        self.is_const
     }
 
     pub const fn initializer(&self) -> Option<&Exprs> {
@@ -134,17 +411,112 @@ impl Var {
     }
 }
 
+/// `var [a, b, ...rest] = list;` or `var {x, y} = instance;`, also with `const`
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq, Hash)]
+pub struct Destructure {
+    keyword: Token,
+    pattern: Unpack,
+    value: Exprs,
+    is_const: bool,
+    span: Span,
+}
+
+impl Destructure {
+    pub const fn new(keyword: Token, pattern: Unpack, value: Exprs) -> Self {
+        Self {
+            keyword,
+            pattern,
+            value,
+            is_const: false,
+            span: Span::new(0, 0),
+        }
+    }
+
+    #[must_use]
+    pub const fn with_const(mut self, is_const: bool) -> Self {
+        self.is_const = is_const;
+        self
+    }
+
+    pub const fn is_const(&self) -> bool {
+        self.is_const
+    }
+
+    pub const fn keyword(&self) -> &Token {
+        &self.keyword
+    }
+
+    pub const fn pattern(&self) -> &Unpack {
+        &self.pattern
+    }
+
+    pub const fn value(&self) -> &Exprs {
+        &self.value
+    }
+}
+
+/// The names a [`Destructure`] binds
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq, Hash)]
+pub enum Unpack {
+    /// `[a, b, ...rest]`, `rest` gets a list of the elements left over,
+    /// a shape mismatch is reported at the `[`
+    List {
+        bracket: Token,
+        names: Vec<Token>,
+        rest: Option<Token>,
+    },
+    /// `{x, y}`, each name reads the property of the same name
+    Fields { brace: Token, names: Vec<Token> },
+}
+
+impl Unpack {
+    /// Every bound name, `rest` last
+    pub fn names(&self) -> impl Iterator<Item = &Token> {
+        let (names, rest) = match self {
+            Self::List { names, rest, .. } => (names, rest.as_ref()),
+            Self::Fields { names, .. } => (names, None),
+        };
+        names.iter().chain(rest)
+    }
+}
+
+impl std::fmt::Display for Unpack {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names = match self {
+            Self::List { names, .. } | Self::Fields { names, .. } => names,
+        };
+        let mut names: Vec<_> = names.iter().map(|name| name.lexeme().to_owned()).collect();
+        match self {
+            Self::List { rest, .. } => {
+                if let Some(rest) = rest {
+                    names.push(format!("...{}", rest.lexeme()));
+                }
+                write!(f, "[{}]", names.join(", "))
+            },
+            Self::Fields { .. } => write!(f, "{{{}}}", names.join(", ")),
+        }
+    }
+}
+
 #[derive(Clone)]
 #[derive(Debug)]
 #[derive(Default)]
 #[derive(PartialEq, Eq, Hash)]
 pub struct Block {
     statements: Vec<Stmts>,
+    span: Span,
 }
 
 impl Block {
     pub const fn new(statements: Vec<Stmts>) -> Self {
-        Self { statements }
+        Self {
+            statements,
+            span: Span::new(0, 0),
+        }
     }
 
     pub fn statements(&self) -> &[Stmts] {
@@ -159,6 +531,7 @@ pub struct If {
     condition: Exprs,
     then_branch: Box<Stmts>,
     else_branch: Option<Box<Stmts>>,
+    span: Span,
 }
 
 impl If {
@@ -170,6 +543,7 @@ impl If {
             condition,
             then_branch,
             else_branch: else_branch.into(),
+            span: Span::new(0, 0),
         }
     }
 
@@ -192,11 +566,29 @@ impl If {
 pub struct While {
     condition: Exprs,
     body: Box<Stmts>,
+    /// The increment clause of a desugared `for`, runs after the body even on `continue`
+    increment: Option<Exprs>,
+    span: Span,
 }
 
 impl While {
     pub const fn new(condition: Exprs, body: Box<Stmts>) -> Self {
-        Self { condition, body }
+        Self {
+            condition,
+            body,
+            increment: None,
+            span: Span::new(0, 0),
+        }
+    }
+
+    #[must_use]
+    pub fn with_increment(mut self, increment: Exprs) -> Self {
+        self.increment = Some(increment);
+        self
+    }
+
+    pub const fn increment(&self) -> Option<&Exprs> {
+        self.increment.as_ref()
     }
 
     pub const fn condition(&self) -> &Exprs {
@@ -213,11 +605,15 @@ impl While {
 #[derive(PartialEq, Eq, Hash, PartialOrd)]
 pub struct Break {
     lexeme: Token,
+    span: Span,
 }
 
 impl Break {
     pub const fn new(lexeme: Token) -> Self {
-        Self { lexeme }
+        Self {
+            lexeme,
+            span: Span::new(0, 0),
+        }
     }
 
     pub const fn token(&self) -> &Token {
@@ -225,6 +621,90 @@ impl Break {
     }
 }
 
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq, Hash, PartialOrd)]
+pub struct Continue {
+    lexeme: Token,
+    span: Span,
+}
+
+impl Continue {
+    pub const fn new(lexeme: Token) -> Self {
+        Self {
+            lexeme,
+            span: Span::new(0, 0),
+        }
+    }
+
+    pub const fn token(&self) -> &Token {
+        &self.lexeme
+    }
+}
+
+/// `match (value) { pattern => stmt, ... }`, arms are tried in order
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq, Hash)]
+pub struct Match {
+    keyword: Token,
+    value: Exprs,
+    arms: Vec<MatchArm>,
+    span: Span,
+}
+
+impl Match {
+    pub const fn new(keyword: Token, value: Exprs, arms: Vec<MatchArm>) -> Self {
+        Self {
+            keyword,
+            value,
+            arms,
+            span: Span::new(0, 0),
+        }
+    }
+
+    pub const fn keyword(&self) -> &Token {
+        &self.keyword
+    }
+
+    pub const fn value(&self) -> &Exprs {
+        &self.value
+    }
+
+    pub fn arms(&self) -> &[MatchArm] {
+        &self.arms
+    }
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq, Hash)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub body: Stmts,
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq, Hash)]
+pub enum Pattern {
+    /// `1`, `"x"`, `true`, `nil`, equal to the value
+    Literal { value: LiteralType, token: Token },
+    /// `Point p`, an instance of the class or a subclass, bound to `p`
+    Class { class: Variable, binding: Token },
+    /// `_`, matches anything
+    Wildcard(Token),
+}
+
+impl Pattern {
+    pub const fn token(&self) -> &Token {
+        match self {
+            Self::Literal { token, .. } | Self::Wildcard(token) => token,
+            Self::Class { binding, .. } => binding,
+        }
+    }
+}
+
 macro_rules! statement_gen {
     ($($stm:ident), *,) => {
 paste::paste! {
@@ -253,6 +733,39 @@ $(
 )*
 }
 
+impl Stmts {
+    pub const fn span(&self) -> Span {
+        match self {
+        $(
+            Self::$stm(inner) => inner.span,
+        )*
+        }
+    }
+
+    #[must_use]
+    pub fn with_span(self, span: Span) -> Self {
+        match self {
+        $(
+            Self::$stm(inner) => Self::$stm(inner.with_span(span)),
+        )*
+        }
+    }
+}
+
+$(
+    impl $stm {
+        pub const fn span(&self) -> Span {
+            self.span
+        }
+
+        #[must_use]
+        pub const fn with_span(mut self, span: Span) -> Self {
+            self.span = span;
+            self
+        }
+    }
+)*
+
 impl Stmt for Stmts {
     #[inline]
     fn accept<R>(&self, visitor: &mut dyn StmtVisitor<R>) -> R {
@@ -270,9 +783,63 @@ impl Stmt for Stmts {
 }
 
 statement_gen!(
-    Expression, Print, Var, Block, If, While, Break, Function, Return, Class,
+    Expression,
+    Print,
+    Var,
+    Block,
+    If,
+    While,
+    Break,
+    Continue,
+    Function,
+    Return,
+    Class,
+    Match,
+    Trait,
+    Import,
+    Throw,
+    Try,
+    Yield,
+    Destructure,
 );
 
+impl Stmts {
+    /// Whether a `yield` runs as part of this statement, the bodies of nested functions are
+    /// their own
+    pub fn has_yield(&self) -> bool {
+        match self {
+            Self::Yield(_) => true,
+            Self::Block(block) => block.statements().iter().any(Self::has_yield),
+            Self::If(if_) => {
+                if_.then_branch().has_yield() || if_.else_branch().is_some_and(Self::has_yield)
+            },
+            Self::While(while_) => while_.body().has_yield(),
+            Self::Match(match_) => match_.arms().iter().any(|arm| arm.body.has_yield()),
+            Self::Try(try_) => {
+                try_.body().iter().any(Self::has_yield)
+                    || try_
+                        .catch()
+                        .is_some_and(|catch| catch.body.iter().any(Self::has_yield))
+                    || try_
+                        .finally()
+                        .is_some_and(|finally| finally.iter().any(Self::has_yield))
+            },
+            Self::Expression(_)
+            | Self::Print(_)
+            | Self::Var(_)
+            | Self::Destructure(_)
+            | Self::Break(_)
+            | Self::Continue(_)
+            | Self::Function(_)
+            | Self::Return(_)
+            | Self::Class(_)
+            | Self::Trait(_)
+            | Self::Import(_)
+            | Self::Throw(_) => false,
+        }
+    }
+}
+
 impl From<Stmts> for Option<Box<Stmts>> {
     fn from(val: Stmts) -> Self {
         Some(Box::new(val))
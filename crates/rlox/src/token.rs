@@ -1,148 +1,322 @@
 #![allow(unfulfilled_lint_expectations, reason = "allow it")]
 
-use std::{fmt::Display, rc::Rc};
+use std::{fmt::Display, ops::Range, rc::Rc};
+
+/// The scanned source text, shared by all its tokens.
+///
+/// Keeps the byte offset of every line start, so a token can find its line with a binary search
+/// instead of rescanning the text.
+#[derive(Clone)]
+#[derive(Debug)]
+pub struct Source(Rc<SourceInner>);
+
+#[derive(Debug)]
+struct SourceInner {
+    text: Box<str>,
+    line_starts: Box<[usize]>,
+}
+
+impl Source {
+    pub fn new(text: &str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(idx, _)| idx + 1))
+            .collect();
+        Self(Rc::new(SourceInner {
+            text: Box::from(text),
+            line_starts,
+        }))
+    }
+
+    pub fn text(&self) -> &str {
+        &self.0.text
+    }
+
+    /// 1-based line of a byte offset
+    pub fn line(&self, offset: usize) -> usize {
+        self.0.line_starts.partition_point(|&start| start <= offset)
+    }
+
+    /// 1-based line and column of a byte offset, column is counted in chars
+    pub fn line_col(&self, offset: usize) -> (usize, usize) {
+        let line = self.line(offset);
+        let line_start = self
+            .0
+            .line_starts
+            .get(line.saturating_sub(1))
+            .copied()
+            .unwrap_or_default();
+        let col = self
+            .text()
+            .get(line_start..offset)
+            .map_or(0, |before| before.chars().count());
+        (line, col + 1)
+    }
+}
+
+impl Default for Source {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
+impl From<Rc<str>> for Source {
+    fn from(text: Rc<str>) -> Self {
+        Self::new(&text)
+    }
+}
+
+impl From<&str> for Source {
+    fn from(text: &str) -> Self {
+        Self::new(text)
+    }
+}
+
+// the line table is derived from the text, so only compare the text
+impl PartialEq for Source {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0) || self.text() == other.text()
+    }
+}
+
+impl Eq for Source {}
+
+impl PartialOrd for Source {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Source {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.text().cmp(other.text())
+    }
+}
+
+impl std::hash::Hash for Source {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.text().hash(state);
+    }
+}
 
 #[derive(Clone)]
 #[derive(Debug)]
 #[derive(Default)]
 #[derive(PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct TokenInner {
-    origin: Rc<str>,
+    origin: Source,
     lexeme: Rc<str>,
-    /// start char offset
+    /// start byte offset
     offset: usize,
+    /// byte length in the source
+    len: usize,
 }
 
 impl TokenInner {
-    pub fn new_string(origin: Rc<str>, len: usize, offset: usize) -> Self {
+    pub fn new_string<O: Into<Source>>(origin: O, len: usize, offset: usize) -> Self {
+        let origin = origin.into();
         // plus 1 trim start '"'
         let trim = offset + 1;
-        let lexeme: Rc<str> = Rc::from(&origin[trim..trim + len]);
+        let lexeme: Rc<str> = Rc::from(&origin.text()[trim..trim + len]);
         Self {
             origin,
             lexeme,
             offset,
+            // with the two '"'
+            len: len + 2,
+        }
+    }
+
+    /// `raw_len` is the length between the '"', `value` is the string after escaping
+    pub fn new_escaped_string<O: Into<Source>>(
+        origin: O,
+        raw_len: usize,
+        offset: usize,
+        value: &str,
+    ) -> Self {
+        // with the two '"'
+        Self::new_cooked(origin, raw_len + 2, offset, value)
+    }
+
+    /// The lexeme is not a slice of the source, e.g. a string after escaping.
+    /// `len` is the length in the source
+    pub fn new_cooked<O: Into<Source>>(origin: O, len: usize, offset: usize, lexeme: &str) -> Self {
+        Self {
+            origin: origin.into(),
+            lexeme: Rc::from(lexeme),
+            offset,
+            len,
         }
     }
 
-    pub fn new_true(origin: Rc<str>, offset: usize) -> Self {
+    pub fn new_true<O: Into<Source>>(origin: O, offset: usize) -> Self {
         Self::new(origin, "true".len(), offset)
     }
 
-    pub fn new_class(origin: Rc<str>, offset: usize) -> Self {
+    pub fn new_class<O: Into<Source>>(origin: O, offset: usize) -> Self {
         Self::new(origin, "class".len(), offset)
     }
 
-    pub fn new_var(origin: Rc<str>, offset: usize) -> Self {
+    pub fn new_var<O: Into<Source>>(origin: O, offset: usize) -> Self {
         Self::new(origin, "var".len(), offset)
     }
 
-    pub fn new_print(origin: Rc<str>, offset: usize) -> Self {
+    pub fn new_print<O: Into<Source>>(origin: O, offset: usize) -> Self {
         Self::new(origin, "print".len(), offset)
     }
 
-    pub fn new_greater_equal(origin: Rc<str>, offset: usize) -> Self {
+    pub fn new_greater_equal<O: Into<Source>>(origin: O, offset: usize) -> Self {
         Self::new(origin, ">=".len(), offset)
     }
 
-    pub fn new_greater(origin: Rc<str>, offset: usize) -> Self {
+    pub fn new_greater<O: Into<Source>>(origin: O, offset: usize) -> Self {
         Self::new(origin, ">".len(), offset)
     }
 
-    pub fn new_less_equal(origin: Rc<str>, offset: usize) -> Self {
+    pub fn new_less_equal<O: Into<Source>>(origin: O, offset: usize) -> Self {
         Self::new(origin, "<=".len(), offset)
     }
 
-    pub fn new_less(origin: Rc<str>, offset: usize) -> Self {
+    pub fn new_less<O: Into<Source>>(origin: O, offset: usize) -> Self {
         Self::new(origin, "<".len(), offset)
     }
 
-    pub fn new_equal_equal(origin: Rc<str>, offset: usize) -> Self {
+    pub fn new_equal_equal<O: Into<Source>>(origin: O, offset: usize) -> Self {
         Self::new(origin, "==".len(), offset)
     }
 
-    pub fn new_equal(origin: Rc<str>, offset: usize) -> Self {
+    pub fn new_equal<O: Into<Source>>(origin: O, offset: usize) -> Self {
         Self::new(origin, "=".len(), offset)
     }
 
-    pub fn new_bang_equal(origin: Rc<str>, offset: usize) -> Self {
+    pub fn new_dot_dot_dot<O: Into<Source>>(origin: O, offset: usize) -> Self {
+        Self::new(origin, "...".len(), offset)
+    }
+
+    pub fn new_fat_arrow<O: Into<Source>>(origin: O, offset: usize) -> Self {
+        Self::new(origin, "=>".len(), offset)
+    }
+
+    /// `+=`, `-=`, `*=`, `/=`, `%=`
+    pub fn new_compound_equal<O: Into<Source>>(origin: O, offset: usize) -> Self {
+        Self::new(origin, 2, offset)
+    }
+
+    /// `++`, `--`
+    pub fn new_increment<O: Into<Source>>(origin: O, offset: usize) -> Self {
+        Self::new(origin, 2, offset)
+    }
+
+    pub fn new_bang_equal<O: Into<Source>>(origin: O, offset: usize) -> Self {
         Self::new(origin, "!=".len(), offset)
     }
 
-    pub fn new_bang(origin: Rc<str>, offset: usize) -> Self {
+    pub fn new_bang<O: Into<Source>>(origin: O, offset: usize) -> Self {
         Self::new(origin, '!'.len_utf8(), offset)
     }
 
-    pub fn new_slash(origin: Rc<str>, offset: usize) -> Self {
+    pub fn new_slash<O: Into<Source>>(origin: O, offset: usize) -> Self {
         Self::new(origin, '/'.len_utf8(), offset)
     }
 
-    pub fn new_star(origin: Rc<str>, offset: usize) -> Self {
+    pub fn new_star<O: Into<Source>>(origin: O, offset: usize) -> Self {
         Self::new(origin, '*'.len_utf8(), offset)
     }
 
-    pub fn new_semicolon(origin: Rc<str>, offset: usize) -> Self {
+    pub fn new_question<O: Into<Source>>(origin: O, offset: usize) -> Self {
+        Self::new(origin, '?'.len_utf8(), offset)
+    }
+
+    pub fn new_colon<O: Into<Source>>(origin: O, offset: usize) -> Self {
+        Self::new(origin, ':'.len_utf8(), offset)
+    }
+
+    pub fn new_percent<O: Into<Source>>(origin: O, offset: usize) -> Self {
+        Self::new(origin, '%'.len_utf8(), offset)
+    }
+
+    pub fn new_star_star<O: Into<Source>>(origin: O, offset: usize) -> Self {
+        Self::new(origin, "**".len(), offset)
+    }
+
+    pub fn new_semicolon<O: Into<Source>>(origin: O, offset: usize) -> Self {
         Self::new(origin, ';'.len_utf8(), offset)
     }
 
-    pub fn new_plus(origin: Rc<str>, offset: usize) -> Self {
+    pub fn new_plus<O: Into<Source>>(origin: O, offset: usize) -> Self {
         Self::new(origin, '+'.len_utf8(), offset)
     }
 
-    pub fn new_minus(origin: Rc<str>, offset: usize) -> Self {
+    pub fn new_minus<O: Into<Source>>(origin: O, offset: usize) -> Self {
         Self::new(origin, '-'.len_utf8(), offset)
     }
 
-    pub fn new_dot(origin: Rc<str>, offset: usize) -> Self {
+    pub fn new_dot<O: Into<Source>>(origin: O, offset: usize) -> Self {
         Self::new(origin, '.'.len_utf8(), offset)
     }
 
-    pub fn new_comma(origin: Rc<str>, offset: usize) -> Self {
+    pub fn new_comma<O: Into<Source>>(origin: O, offset: usize) -> Self {
         Self::new(origin, ','.len_utf8(), offset)
     }
 
-    pub fn new_left_brace(origin: Rc<str>, offset: usize) -> Self {
+    pub fn new_left_brace<O: Into<Source>>(origin: O, offset: usize) -> Self {
         Self::new(origin, '{'.len_utf8(), offset)
     }
 
-    pub fn new_right_brace(origin: Rc<str>, offset: usize) -> Self {
+    pub fn new_right_brace<O: Into<Source>>(origin: O, offset: usize) -> Self {
         Self::new(origin, '}'.len_utf8(), offset)
     }
 
-    pub fn new_left_paren(origin: Rc<str>, offset: usize) -> Self {
+    pub fn new_left_bracket<O: Into<Source>>(origin: O, offset: usize) -> Self {
+        Self::new(origin, '['.len_utf8(), offset)
+    }
+
+    pub fn new_right_bracket<O: Into<Source>>(origin: O, offset: usize) -> Self {
+        Self::new(origin, ']'.len_utf8(), offset)
+    }
+
+    pub fn new_left_paren<O: Into<Source>>(origin: O, offset: usize) -> Self {
         Self::new(origin, '('.len_utf8(), offset)
     }
 
-    pub fn new_right_paren(origin: Rc<str>, offset: usize) -> Self {
+    pub fn new_right_paren<O: Into<Source>>(origin: O, offset: usize) -> Self {
         Self::new(origin, ')'.len_utf8(), offset)
     }
 
-    pub fn new(origin: Rc<str>, len: usize, offset: usize) -> Self {
-        let lexeme: Rc<str> = Rc::from(&origin[offset..offset + len]);
+    pub fn new<O: Into<Source>>(origin: O, len: usize, offset: usize) -> Self {
+        let origin = origin.into();
+        let lexeme: Rc<str> = Rc::from(&origin.text()[offset..offset + len]);
         Self {
             origin,
             lexeme,
             offset,
+            len,
         }
     }
 
-    pub fn new_invalid(origin: Rc<str>, len: usize, offset: usize) -> Self {
+    pub fn new_invalid<O: Into<Source>>(origin: O, len: usize, offset: usize) -> Self {
         Self::new(origin, len, offset)
     }
 
+    /// (line, column) of the token start, both 1-based
     pub fn get_xy(&self) -> (usize, usize) {
-        let mut line = 1;
-        let mut col = 1;
-        for (_, ch) in self.origin.char_indices().take(self.offset) {
-            if ch == '\n' {
-                line += 1;
-                col = 1;
-            }
-            else {
-                col += 1;
-            }
-        }
-        (line, col)
+        self.origin.line_col(self.offset)
+    }
+
+    pub fn line(&self) -> usize {
+        self.origin.line(self.offset)
+    }
+
+    /// Byte range of the token in the source
+    pub const fn span(&self) -> Range<usize> {
+        self.offset..self.offset + self.len
+    }
+
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub const fn origin(&self) -> &Source {
+        &self.origin
     }
 
     pub fn lexeme(&self) -> &str {
@@ -160,59 +334,234 @@ impl TokenInner {
 #[derive(PartialEq, PartialOrd)]
 pub enum Token {
     // Single_character tokens
-    LeftParen { inner: TokenInner },
-    RightParen { inner: TokenInner },
-    LeftBrace { inner: TokenInner },
-    RightBrace { inner: TokenInner },
-    Comma { inner: TokenInner },
-    Dot { inner: TokenInner },
-    Minus { inner: TokenInner },
-    Plus { inner: TokenInner },
-    Semicolon { inner: TokenInner },
-    Slash { inner: TokenInner },
-    Star { inner: TokenInner },
+    LeftParen {
+        inner: TokenInner,
+    },
+    RightParen {
+        inner: TokenInner,
+    },
+    LeftBrace {
+        inner: TokenInner,
+    },
+    RightBrace {
+        inner: TokenInner,
+    },
+    LeftBracket {
+        inner: TokenInner,
+    },
+    RightBracket {
+        inner: TokenInner,
+    },
+    Comma {
+        inner: TokenInner,
+    },
+    Dot {
+        inner: TokenInner,
+    },
+    Minus {
+        inner: TokenInner,
+    },
+    Plus {
+        inner: TokenInner,
+    },
+    Semicolon {
+        inner: TokenInner,
+    },
+    Slash {
+        inner: TokenInner,
+    },
+    Star {
+        inner: TokenInner,
+    },
+    Percent {
+        inner: TokenInner,
+    },
+    Question {
+        inner: TokenInner,
+    },
+    Colon {
+        inner: TokenInner,
+    },
 
     // One or two character tokens.
-    Bang { inner: TokenInner },         // !
-    BangEqual { inner: TokenInner },    // !=
-    Equal { inner: TokenInner },        // =
-    EqualEqual { inner: TokenInner },   // ==
-    Greater { inner: TokenInner },      // >
-    GreaterEqual { inner: TokenInner }, // >=
-    Less { inner: TokenInner },         // <
-    LessEqual { inner: TokenInner },    // <=
+    Bang {
+        inner: TokenInner,
+    }, // !
+    BangEqual {
+        inner: TokenInner,
+    }, // !=
+    Equal {
+        inner: TokenInner,
+    }, // =
+    EqualEqual {
+        inner: TokenInner,
+    }, // ==
+    FatArrow {
+        inner: TokenInner,
+    }, // =>
+    DotDotDot {
+        inner: TokenInner,
+    }, // ...
+    PlusEqual {
+        inner: TokenInner,
+    }, // +=
+    MinusEqual {
+        inner: TokenInner,
+    }, // -=
+    StarEqual {
+        inner: TokenInner,
+    }, // *=
+    SlashEqual {
+        inner: TokenInner,
+    }, // /=
+    PercentEqual {
+        inner: TokenInner,
+    }, // %=
+    StarStar {
+        inner: TokenInner,
+    }, // **
+    PlusPlus {
+        inner: TokenInner,
+    }, // ++
+    MinusMinus {
+        inner: TokenInner,
+    }, // --
+    Greater {
+        inner: TokenInner,
+    }, // >
+    GreaterEqual {
+        inner: TokenInner,
+    }, // >=
+    Less {
+        inner: TokenInner,
+    }, // <
+    LessEqual {
+        inner: TokenInner,
+    }, // <=
 
     // Literals
-    Identifier { inner: TokenInner },
-    String { inner: TokenInner },
-    Number { double: f64, inner: TokenInner },
+    Identifier {
+        inner: TokenInner,
+    },
+    String {
+        inner: TokenInner,
+    },
+    /// `"...${` or `}...${`, a string segment followed by an embedded expression
+    Interpolation {
+        inner: TokenInner,
+    },
+    Number {
+        double: f64,
+        inner: TokenInner,
+    },
 
     // Keywords
-    And { inner: TokenInner },
-    Class { inner: TokenInner },
-    Else { inner: TokenInner },
-    Fun { inner: TokenInner },
-    For { inner: TokenInner },
-    If { inner: TokenInner },
-    Nil { inner: TokenInner },
-    Or { inner: TokenInner },
-    Print { inner: TokenInner },
-    Return { inner: TokenInner },
-    Super { inner: TokenInner },
-    This { inner: TokenInner },
-    True { inner: TokenInner },
-    False { inner: TokenInner },
-    Var { inner: TokenInner },
-    While { inner: TokenInner },
-
-    Eof { inner: TokenInner },
-
-    Comment { inner: TokenInner },
-    BlockComment { inner: TokenInner },
-
-    Break { inner: TokenInner },
-
-    Invalid { inner: TokenInner },
+    And {
+        inner: TokenInner,
+    },
+    Class {
+        inner: TokenInner,
+    },
+    Else {
+        inner: TokenInner,
+    },
+    Fun {
+        inner: TokenInner,
+    },
+    For {
+        inner: TokenInner,
+    },
+    If {
+        inner: TokenInner,
+    },
+    Nil {
+        inner: TokenInner,
+    },
+    Or {
+        inner: TokenInner,
+    },
+    Print {
+        inner: TokenInner,
+    },
+    Return {
+        inner: TokenInner,
+    },
+    Super {
+        inner: TokenInner,
+    },
+    This {
+        inner: TokenInner,
+    },
+    True {
+        inner: TokenInner,
+    },
+    False {
+        inner: TokenInner,
+    },
+    Var {
+        inner: TokenInner,
+    },
+    While {
+        inner: TokenInner,
+    },
+
+    Eof {
+        inner: TokenInner,
+    },
+
+    Comment {
+        inner: TokenInner,
+    },
+    BlockComment {
+        inner: TokenInner,
+    },
+
+    Break {
+        inner: TokenInner,
+    },
+    Continue {
+        inner: TokenInner,
+    },
+    Match {
+        inner: TokenInner,
+    },
+    In {
+        inner: TokenInner,
+    },
+    Trait {
+        inner: TokenInner,
+    },
+    With {
+        inner: TokenInner,
+    },
+    Const {
+        inner: TokenInner,
+    },
+    Import {
+        inner: TokenInner,
+    },
+    As {
+        inner: TokenInner,
+    },
+    Throw {
+        inner: TokenInner,
+    },
+    Try {
+        inner: TokenInner,
+    },
+    Catch {
+        inner: TokenInner,
+    },
+    Finally {
+        inner: TokenInner,
+    },
+    Yield {
+        inner: TokenInner,
+    },
+
+    Invalid {
+        inner: TokenInner,
+    },
 }
 
 impl std::hash::Hash for Token {
@@ -234,6 +583,8 @@ impl std::hash::Hash for Token {
             RightParen,
             LeftBrace,
             RightBrace,
+            LeftBracket,
+            RightBracket,
             Comma,
             Dot,
             Minus,
@@ -241,16 +592,30 @@ impl std::hash::Hash for Token {
             Semicolon,
             Slash,
             Star,
+            Percent,
+            Question,
+            Colon,
             Bang,
             BangEqual,
             Equal,
             EqualEqual,
+            FatArrow,
+            DotDotDot,
+            PlusEqual,
+            MinusEqual,
+            StarEqual,
+            SlashEqual,
+            PercentEqual,
+            StarStar,
+            PlusPlus,
+            MinusMinus,
             Greater,
             GreaterEqual,
             Less,
             LessEqual,
             Identifier,
             String,
+            Interpolation,
             And,
             Class,
             Else,
@@ -271,6 +636,19 @@ impl std::hash::Hash for Token {
             Comment,
             BlockComment,
             Break,
+            Continue,
+            Match,
+            In,
+            Trait,
+            With,
+            Const,
+            Import,
+            As,
+            Throw,
+            Try,
+            Catch,
+            Finally,
+            Yield,
             Invalid,
         );
     }
@@ -345,6 +723,8 @@ token_enums_match!(
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
     Comma,
     Dot,
     Minus,
@@ -352,16 +732,30 @@ token_enums_match!(
     Semicolon,
     Slash,
     Star,
+    Percent,
+    Question,
+    Colon,
     Bang,
     BangEqual,
     Equal,
     EqualEqual,
+    FatArrow,
+    DotDotDot,
+    PlusEqual,
+    MinusEqual,
+    StarEqual,
+    SlashEqual,
+    PercentEqual,
+    StarStar,
+    PlusPlus,
+    MinusMinus,
     Greater,
     GreaterEqual,
     Less,
     LessEqual,
     Identifier,
     String,
+    Interpolation,
     Number,
     And,
     Class,
@@ -384,6 +778,19 @@ token_enums_match!(
     BlockComment,
     Invalid,
     Break,
+    Continue,
+    Match,
+    In,
+    Trait,
+    With,
+    Const,
+    Import,
+    As,
+    Throw,
+    Try,
+    Catch,
+    Finally,
+    Yield,
 );
 
 impl Token {
@@ -404,7 +811,8 @@ impl Token {
 
         match_arms!(
             And, Class, Else, Fun, For, If, Nil, Or, Print, Return, Super, This, True, False, Var,
-            While, Break
+            While, Break, Continue, Match, In, Trait, With, Const, Import, As, Throw, Try, Catch,
+            Finally, Yield
         )
     }
 }